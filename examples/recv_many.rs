@@ -0,0 +1,55 @@
+//! Compares receiving one message at a time with receiving in batches.
+//!
+//! Run with `cargo run --release --example recv_many`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use trotcast::prelude::*;
+
+const CAPACITY: usize = 1024;
+const ROUNDS: usize = 2000;
+
+fn main() {
+    let one_by_one = bench(|rx, buf| {
+        while let Ok(msg) = rx.try_recv() {
+            buf.push(msg);
+        }
+    });
+    let many = bench(|rx, buf| while rx.try_recv_many(buf, 256).is_ok() {});
+    let drain = bench(|rx, buf| buf.extend(rx.drain()));
+
+    println!("try_recv:      {:>6.2} ns/msg", per_msg(one_by_one));
+    println!("try_recv_many: {:>6.2} ns/msg", per_msg(many));
+    println!("drain:         {:>6.2} ns/msg", per_msg(drain));
+}
+
+/// Fills the channel, and times `recv` emptying it into a buffer.
+fn bench(mut recv: impl FnMut(&mut Receiver<u64>, &mut Vec<u64>)) -> Duration {
+    let tx = Channel::new(CAPACITY);
+    let mut rx = tx.spawn_rx();
+    // a second receiver, so messages are cloned like with any broadcast
+    let mut other = tx.spawn_rx();
+    let mut buf = Vec::with_capacity(CAPACITY);
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        for i in 0..CAPACITY as u64 {
+            tx.send(i).unwrap();
+        }
+        let start = Instant::now();
+        recv(&mut rx, &mut buf);
+        elapsed += start.elapsed();
+        assert_eq!(buf.len(), CAPACITY);
+        black_box(&buf);
+        buf.clear();
+        while other.try_recv_many(&mut buf, CAPACITY).is_ok() {}
+        buf.clear();
+    }
+    elapsed
+}
+
+fn per_msg(elapsed: Duration) -> f64 {
+    elapsed.as_nanos() as f64 / (ROUNDS * CAPACITY) as f64
+}
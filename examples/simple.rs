@@ -55,7 +55,7 @@ fn main() {
                     Ok(msg) => {
                         info!("RX1 msg: {msg}");
 
-                        _ = tx.send((1, msg, count, rx_1.head));
+                        _ = tx.send((1, msg, count, rx_1.position()));
                         count += 1;
                     }
                     Err(TryRecvError::Disconnected) => {
@@ -74,7 +74,7 @@ fn main() {
             let mut count = 0;
             while let Ok(msg) = rx_2.recv() {
                 info!("RX2({count}) msg: {msg}");
-                _ = tx.send((2, msg, count, rx_2.head));
+                _ = tx.send((2, msg, count, rx_2.position()));
                 count += 1;
             }
         }
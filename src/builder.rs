@@ -0,0 +1,231 @@
+use core::marker::PhantomData;
+
+use alloc::{boxed::Box, sync::Arc};
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::prelude::*;
+
+/// What a [`Channel`] does when a send would overwrite a message that some
+/// receiver hasn't read yet.
+///
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let tx = Channel::builder(2).overflow(Overflow::Lag).build();
+/// let mut slow = tx.spawn_rx();
+///
+/// // a slow receiver never holds up the sender
+/// for i in 0..5 {
+///     tx.send(i).unwrap();
+/// }
+///
+/// assert_eq!(slow.try_recv(), Err(TryRecvError::Lagged(3)));
+/// assert_eq!(slow.try_recv(), Ok(3));
+/// assert_eq!(slow.try_recv(), Ok(4));
+/// assert_eq!(slow.try_recv(), Err(TryRecvError::Empty));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The channel is full. Senders wait for the slowest receiver, and
+    /// [`Channel::send`] returns [`SendError::Full`].
+    #[default]
+    Block,
+    /// Sends always succeed, overwriting the oldest message. A receiver that falls
+    /// more than `capacity` messages behind gets [`RecvError::Lagged`] with the number
+    /// of messages it missed, and picks up again at the oldest message still available.
+    Lag,
+    /// Senders wait for the slowest receiver, until the [`Eviction`] policy decides it
+    /// has held up the channel for too long. Every receiver that hasn't read the oldest
+    /// message is then detached, as if it had been dropped, and its next receive returns
+    /// [`RecvError::Evicted`]. It can rejoin with [`Receiver::resubscribe`].
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::builder(2)
+    ///     .overflow(Overflow::Evict(Eviction::FailedSends(3)))
+    ///     .build();
+    /// let mut stuck = tx.spawn_rx();
+    /// let mut live = tx.spawn_rx();
+    ///
+    /// for i in 0..2 {
+    ///     tx.send(i).unwrap();
+    ///     assert_eq!(live.recv(), Ok(i));
+    /// }
+    /// // `stuck` holds up the channel. the third failed send evicts it.
+    /// assert_eq!(tx.send(2), Err(SendError::Full(2)));
+    /// assert_eq!(tx.send(2), Err(SendError::Full(2)));
+    /// tx.send(2).unwrap();
+    /// assert_eq!(live.recv(), Ok(2));
+    ///
+    /// assert_eq!(stuck.try_recv(), Err(TryRecvError::Evicted));
+    /// stuck.resubscribe();
+    /// tx.send(3).unwrap();
+    /// assert_eq!(stuck.try_recv(), Ok(3));
+    /// ```
+    Evict(Eviction),
+    /// Sends always succeed, and nothing is ever dropped. When the channel is full, a
+    /// new segment of `capacity` seats is added for the next messages. A segment is
+    /// freed once every receiver has read past it.
+    ///
+    /// See [`Channel::unbounded`].
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::builder(8).overflow(Overflow::Grow).build();
+    /// let mut fast = tx.spawn_rx();
+    /// let slow = tx.spawn_rx();
+    ///
+    /// let payload = Arc::new(());
+    /// for _ in 0..100 {
+    ///     tx.send(Arc::clone(&payload)).unwrap();
+    ///     fast.recv().unwrap();
+    /// }
+    /// // `slow` holds on to every segment
+    /// assert_eq!(Arc::strong_count(&payload), 101);
+    ///
+    /// // with it gone, only the segment `fast` is in is left
+    /// drop(slow);
+    /// assert!(Arc::strong_count(&payload) <= 1 + 8);
+    /// ```
+    Grow,
+}
+
+/// When [`Overflow::Evict`] gives up on the receivers holding up a full channel.
+///
+/// Every send that finds the channel full counts once toward the policy, however long
+/// it then waits. The count starts over once a send gets through.
+///
+/// ```
+/// use std::time::Duration;
+/// use trotcast::prelude::*;
+///
+/// let tx = Channel::builder(1)
+///     .overflow(Overflow::Evict(Eviction::After(Duration::from_millis(20))))
+///     .build();
+/// let mut stuck = tx.spawn_rx();
+/// let mut live = tx.spawn_rx();
+///
+/// tx.send(0).unwrap();
+/// assert_eq!(live.recv(), Ok(0));
+/// // waits for `stuck` until the time is up, then evicts it
+/// tx.blocking_send(1).unwrap();
+/// assert_eq!(live.recv(), Ok(1));
+/// assert_eq!(stuck.recv(), Err(RecvError::Evicted));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    /// After this many sends in a row found the channel full. A send that's already
+    /// waiting doesn't count again, so when nothing else is being sent, use
+    /// [`Eviction::After`] to give up on a receiver that never reads.
+    FailedSends(usize),
+    /// After the channel has been full for this long. A blocked sender, or the task of
+    /// an async one, is woken to evict when the time is up.
+    ///
+    /// Enabled with the `std` feature.
+    #[cfg(feature = "std")]
+    After(Duration),
+}
+
+/// Configures a [`Channel`] before it's created.
+///
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let tx = Channel::builder(8)
+///     .overflow(Overflow::Lag)
+///     .wait_strategy(Backoff::default())
+///     .build();
+/// let mut rx = tx.spawn_rx();
+/// tx.send(1).unwrap();
+/// assert_eq!(rx.recv(), Ok(1));
+/// ```
+pub struct ChannelBuilder<T> {
+    capacity: usize,
+    history: usize,
+    overflow: Overflow,
+    tagger: Option<Tagger<T>>,
+    strategy: Option<Box<dyn WaitStrategy>>,
+    wait_for_reads: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ChannelBuilder<T> {
+    /// A channel holding up to `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            history: 0,
+            overflow: Overflow::default(),
+            tagger: None,
+            strategy: None,
+            wait_for_reads: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// What to do when the channel is full. Defaults to [`Overflow::Block`].
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Keep the last `n` messages in the channel after every receiver has read them,
+    /// for receivers spawned with [`Channel::spawn_rx_with_history`]. They take up
+    /// room on top of the capacity. Defaults to 0.
+    ///
+    /// Kept messages are cloned for every reader, since none of them is the last.
+    pub fn history(mut self, n: usize) -> Self {
+        self.history = n;
+        self
+    }
+
+    /// Tags every message with a bitmask, for receivers that only want some of them.
+    /// See [`Channel::spawn_rx_interested`].
+    ///
+    /// `tagger` is called once per message, while the sender holds the tail. Keep it cheap.
+    pub fn tags(mut self, tagger: impl Fn(&T) -> u64 + Send + Sync + 'static) -> Self {
+        self.tagger = Some(Box::new(tagger));
+        self
+    }
+
+    /// How blocked senders and receivers wait. Defaults to [`Park`] with the `std`
+    /// feature, and [`Spin`] without it.
+    pub fn wait_strategy(mut self, strategy: impl WaitStrategy + 'static) -> Self {
+        self.strategy = Some(Box::new(strategy));
+        self
+    }
+
+    /// With [`Overflow::Lag`], have senders wait for a seat that's being read, instead of
+    /// moving on to a new ring. For [`Watch`], whose receivers never change rings.
+    pub(crate) fn wait_for_reads(mut self) -> Self {
+        self.wait_for_reads = true;
+        self
+    }
+
+    /// Creates the channel.
+    ///
+    /// # Panics
+    /// - if the capacity is 0
+    pub fn build(self) -> Channel<T> {
+        assert!(self.capacity > 0, "Capacity needs to be greater than 0");
+
+        #[cfg(feature = "std")]
+        let strategy = self.strategy.unwrap_or_else(|| Box::new(Park::new()));
+        #[cfg(not(feature = "std"))]
+        let strategy = self.strategy.unwrap_or_else(|| Box::new(Spin));
+
+        let shared = Arc::new(State::new(
+            self.capacity,
+            self.history,
+            self.overflow,
+            self.tagger,
+            strategy,
+            self.wait_for_reads,
+        ));
+        Channel::from_shared_state(shared)
+    }
+}
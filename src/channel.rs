@@ -1,21 +1,69 @@
-use core::sync::atomic::Ordering;
+use core::{
+    ops::DerefMut,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 
 use crate::prelude::*;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 /// A channel handle for the broadcast channel that allows sending messages to all receivers.
 pub struct Channel<T> {
     shared: Arc<State<T>>,
 }
-impl<T: Clone> Channel<T> {
+impl<T> Channel<T> {
     /// Create a new channel
     pub fn new(capacity: usize) -> Self {
-        assert!(capacity > 0, "Capacity needs to be greater than 0");
+        ChannelBuilder::new(capacity).build()
+    }
+
+    /// Create a new channel that grows instead of blocking, so sends never wait.
+    ///
+    /// Memory is taken in segments of 32 messages. Use [`Channel::builder`] with
+    /// [`Overflow::Grow`] for a different segment size.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::unbounded();
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// for i in 0..1000 {
+    ///     tx.send(i).unwrap();
+    /// }
+    /// for i in 0..1000 {
+    ///     assert_eq!(rx.try_recv(), Ok(i));
+    /// }
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    /// ```
+    pub fn unbounded() -> Self {
+        ChannelBuilder::new(32).overflow(Overflow::Grow).build()
+    }
 
-        let shared = Arc::new(State::new(capacity));
+    /// Create a new channel whose blocked senders and receivers wait with `strategy`.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// // latency matters more than a core here
+    /// let tx = Channel::with_wait_strategy(16, Spin);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// std::thread::spawn(move || tx.blocking_send(1).unwrap());
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// ```
+    pub fn with_wait_strategy(capacity: usize, strategy: impl WaitStrategy + 'static) -> Self {
+        ChannelBuilder::new(capacity)
+            .wait_strategy(strategy)
+            .build()
+    }
 
-        Self::from_shared_state(Arc::clone(&shared))
+    /// Configure a new channel. See [`ChannelBuilder`].
+    pub fn builder(capacity: usize) -> ChannelBuilder<T> {
+        ChannelBuilder::new(capacity)
     }
 
     pub(crate) fn from_shared_state(shared: Arc<State<T>>) -> Self {
@@ -42,60 +90,357 @@ impl<T: Clone> Channel<T> {
         Receiver::new(Arc::clone(&self.shared))
     }
 
-    fn send_inner(&self, value: T, blocking: bool) -> Result<(), SendError<T>> {
+    /// Spawns a new [`Receiver`] that first replays up to `n` of the most recent messages,
+    /// out of those kept with [`ChannelBuilder::history`].
+    ///
+    /// Until it reads them, replayed messages count toward the capacity like any other
+    /// unread message. History starts over after [`Channel::set_capacity`], and with [`Overflow::Grow`]
+    /// it only reaches back to the start of the newest segment.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::builder(2).history(3).build();
+    /// let mut rx = tx.spawn_rx();
+    /// for i in 0..4 {
+    ///     tx.send(i).unwrap();
+    ///     assert_eq!(rx.recv(), Ok(i));
+    /// }
+    ///
+    /// let mut late = tx.spawn_rx_with_history(2);
+    /// assert_eq!(late.recv(), Ok(2));
+    /// assert_eq!(late.recv(), Ok(3));
+    /// tx.send(4).unwrap();
+    /// assert_eq!(late.recv(), Ok(4));
+    ///
+    /// // never more than what's kept
+    /// let mut later = tx.spawn_rx_with_history(10);
+    /// assert_eq!(later.recv(), Ok(2));
+    /// ```
+    pub fn spawn_rx_with_history(&self, n: usize) -> Receiver<T> {
+        Receiver::join(Arc::clone(&self.shared), n, Cursor::ALL, 0)
+    }
+
+    /// Spawns a new [`Receiver`] that only gets the messages whose tags share a bit with
+    /// `interest`. Tags come from [`ChannelBuilder::tags`].
+    ///
+    /// Senders don't count it on the other messages, so it doesn't hold them up, and
+    /// nothing is cloned for it. It skips them without reading.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// const ERRORS: u64 = 1 << 0;
+    /// const METRICS: u64 = 1 << 1;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Event {
+    ///     Error(&'static str),
+    ///     Metric(u32),
+    /// }
+    ///
+    /// let tx = Channel::builder(2)
+    ///     .tags(|event: &Event| match event {
+    ///         Event::Error(_) => ERRORS,
+    ///         Event::Metric(_) => METRICS,
+    ///     })
+    ///     .build();
+    /// let mut errors = tx.spawn_rx_interested(ERRORS);
+    /// let mut everything = tx.spawn_rx_interested(ERRORS | METRICS);
+    ///
+    /// for i in 0..2 {
+    ///     tx.send(Event::Metric(i)).unwrap();
+    ///     assert_eq!(everything.recv(), Ok(Event::Metric(i)));
+    /// }
+    /// // `errors` never had to read the metrics, so the channel isn't full
+    /// tx.send(Event::Error("disk full")).unwrap();
+    /// assert_eq!(errors.try_recv(), Ok(Event::Error("disk full")));
+    /// assert_eq!(errors.try_recv(), Err(TryRecvError::Empty));
+    /// ```
+    ///
+    /// # Panics
+    /// - if `interest` is 0
+    pub fn spawn_rx_interested(&self, interest: u64) -> Receiver<T> {
+        assert!(interest != 0, "Interest needs at least one bit set");
+        Receiver::join(Arc::clone(&self.shared), 0, interest, 0)
+    }
+
+    /// Joins the consumer group called `name`, which gets each message once between its
+    /// members. A group that has no members yet starts at the next message sent.
+    ///
+    /// See [`GroupReceiver`].
+    pub fn join_group(&self, name: &str) -> GroupReceiver<T> {
+        GroupReceiver::join(&self.shared, name)
+    }
+
+    /// The number of messages the channel holds. With [`Overflow::Grow`], the size
+    /// of a segment.
+    pub fn capacity(&self) -> usize {
+        self.shared.lock_tail().ring.capacity()
+    }
+
+    /// Changes the number of messages the channel holds, while it's in use.
+    ///
+    /// Messages sent from now on go to a new ring of the new size. The ones already
+    /// sent stay where they are until every receiver has read them, so nobody loses
+    /// their place or misses a message.
+    ///
+    /// # Errors
+    /// - if a receiver has more unread messages than the new capacity holds.
+    ///   Doesn't happen with [`Overflow::Lag`] or [`Overflow::Grow`].
+    ///
+    /// # Panics
+    /// - if the capacity is 0
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// assert_eq!(tx.send(3), Err(SendError::Full(3)));
+    ///
+    /// tx.set_capacity(4).unwrap();
+    /// tx.send(3).unwrap();
+    /// assert_eq!(tx.capacity(), 4);
+    ///
+    /// // too small for what rx hasn't read yet
+    /// assert_eq!(tx.set_capacity(1), Err(ResizeError::Unread(3)));
+    ///
+    /// for i in 1..=3 {
+    ///     assert_eq!(rx.recv(), Ok(i));
+    /// }
+    /// tx.set_capacity(1).unwrap();
+    /// ```
+    pub fn set_capacity(&self, capacity: usize) -> Result<(), ResizeError> {
+        assert!(capacity > 0, "Capacity needs to be greater than 0");
+
+        let mut tail_lock = self.shared.lock_tail();
+        if tail_lock.ring.capacity() == capacity {
+            return Ok(());
+        }
+        if matches!(self.shared.overflow, Overflow::Block | Overflow::Evict(_)) {
+            let unread = tail_lock.unread();
+            if unread > capacity {
+                return Err(ResizeError::Unread(unread));
+            }
+        }
+        tail_lock.replace_ring(capacity);
+        drop(tail_lock);
+
+        // senders waiting on the old ring have room in the new one
+        self.shared.notify_senders();
+        Ok(())
+    }
+
+    fn send_inner(&self, value: T, cond: SendCondition) -> Result<(), InnerSendError<T>> {
+        let mut blocker = self.shared.tx_blocker(cond.deadline());
+        match self.reserve(1, cond, &mut blocker) {
+            Ok(tail_lock) => {
+                self.publish(tail_lock, [value], &blocker);
+                Ok(())
+            }
+            Err(e) => Err(e.with(value)),
+        }
+    }
+
+    fn send_batch_inner(
+        &self,
+        values: Vec<T>,
+        cond: SendCondition,
+    ) -> Result<(), InnerSendError<Vec<T>>> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let mut blocker = self.shared.tx_blocker(cond.deadline());
+        match self.reserve(values.len(), cond, &mut blocker) {
+            Ok(tail_lock) => {
+                self.publish(tail_lock, values, &blocker);
+                Ok(())
+            }
+            Err(e) => Err(e.with(values)),
+        }
+    }
+
+    /// Waits until there's room for `n` messages at the tail, and returns it locked.
+    ///
+    /// Fails with `Full` right away if `n` is more than the channel can ever hold.
+    fn reserve<'a>(
+        &'a self,
+        n: usize,
+        cond: SendCondition,
+        blocker: &mut Blocker<'a>,
+    ) -> Result<impl DerefMut<Target = Tail<T>> + 'a, InnerSendError<()>> {
         if self.shared.num_readers.load(Ordering::Relaxed) == 0 {
-            return Err(SendError::Disconnected(value));
+            return Err(InnerSendError::Disconnected(()));
         }
 
+        // whether this send has yet to count toward evicting
+        let mut new_send = cond != SendCondition::Retry;
         loop {
             // I need sole access to the tail. other writers must wait on me.
+            let mut tail_lock = self.shared.lock_tail();
+            if n > tail_lock.ring.capacity() && self.shared.overflow != Overflow::Grow {
+                return Err(InnerSendError::Full(()));
+            }
+
+            // the fence has not yet been cleared of reads.
+            let room = self.shared.has_room(&tail_lock, n)
+                || self.shared.evict(&mut tail_lock, n, new_send)
+                || self.shared.grow(&mut tail_lock, n);
+            new_send = false;
+            if self.shared.num_readers.load(Ordering::Relaxed) == 0 {
+                // every receiver left or was evicted while we waited for the lock
+                drop(tail_lock);
+                self.shared.notify_senders();
+                return Err(InnerSendError::Disconnected(()));
+            }
+            if room {
+                return Ok(tail_lock);
+            }
+            if matches!(cond, SendCondition::Try | SendCondition::Retry) {
+                return Err(InnerSendError::Full(()));
+            }
             #[cfg(feature = "std")]
-            let mut tail_lock = self.shared.internal_tail.lock().unwrap();
+            blocker.wake_at(self.shared.eviction_due(&tail_lock));
+            // don't hold the lock while waiting, other producers need it to
+            // find out whether they can write.
+            drop(tail_lock);
 
+            let ready = blocker.wait(|| {
+                let tail = self.shared.lock_tail();
+                // a shrunk channel fails on the next go
+                self.shared.num_readers.load(Ordering::Relaxed) == 0
+                    || n > tail.ring.capacity()
+                    || self.shared.has_room(&tail, n)
+            });
+            if self.shared.num_readers.load(Ordering::Relaxed) == 0 {
+                return Err(InnerSendError::Disconnected(()));
+            }
+            #[cfg(feature = "std")]
+            if !ready {
+                return Err(InnerSendError::Timeout(()));
+            }
             #[cfg(not(feature = "std"))]
-            let mut tail_lock = self.shared.internal_tail.lock();
+            let _ = ready;
+        }
+    }
 
-            let fence = (tail_lock.0 + 1) % self.shared.len;
+    /// Writes `values` at the tail, which [`Channel::reserve`] made room for, and then
+    /// moves the tail past all of them at once.
+    fn publish(
+        &self,
+        mut tail_lock: impl DerefMut<Target = Tail<T>>,
+        values: impl IntoIterator<Item = T, IntoIter: ExactSizeIterator>,
+        blocker: &Blocker<'_>,
+    ) {
+        let num_readers = self.shared.num_readers.load(Ordering::SeqCst);
+        let values = values.into_iter();
+        let mut pos = tail_lock.pos;
+        loop {
+            let ring = Arc::clone(&tail_lock.ring);
+            // with `Overflow::Lag` the seats may still have readers, and one may hold on
+            // to its seat with `recv_ref`. rather than wait for them with the tail
+            // locked, leave them this ring and carry on in a new one.
+            let seat_locks = match self.shared.overflow {
+                Overflow::Lag => {
+                    match ring.lock_seats(pos, values.len(), self.shared.wait_for_reads) {
+                        Some(seat_locks) => seat_locks,
+                        None => {
+                            tail_lock.replace_ring(ring.capacity());
+                            continue;
+                        }
+                    }
+                }
+                _ => Vec::new(),
+            };
 
-            let required_reads = unsafe { (&*self.shared.ring[fence].state.get()).required_reads };
+            for value in values {
+                let seat = ring.seat(pos);
+                // only count the receivers that want it
+                let (tags, required_reads) = match &self.shared.tagger {
+                    Some(tagger) => {
+                        let tags = tagger(&value);
+                        let wanted = tail_lock.readers.iter().filter(|cursor| cursor.wants(tags));
+                        (tags, wanted.count())
+                    }
+                    None => (Cursor::ALL, num_readers),
+                };
 
-            // the fence has not yet been cleared of reads.
-            if required_reads
-                .saturating_sub(self.shared.ring[fence].num_reads.load(Ordering::SeqCst))
-                != 0
-            {
-                if blocking {
-                    continue;
-                } else {
-                    return Err(SendError::Full(value));
-                }
-            }
-            let seat = tail_lock.0;
+                // This is free to write!
+                let state = unsafe { &mut *seat.state.get() };
+                state.val = Some(value);
+                state.pos = pos;
+                seat.set_tags(pos, tags);
 
-            // This is free to write!
-            let state = unsafe { &mut *self.shared.ring[seat].state.get() };
-            state.val = Some(value);
+                seat.required_reads.store(required_reads, Ordering::SeqCst);
 
-            state.required_reads = self.shared.num_readers.load(Ordering::SeqCst);
+                seat.num_reads.store(0, Ordering::SeqCst);
+                pos += 1;
+            }
+            drop(seat_locks);
+            break;
+        }
 
-            // set the tail last and then unlock check_writing
-            let tail = (seat + 1) % self.shared.len;
+        // set the tail last, so receivers see every message of a batch together
+        self.shared.tail.store(pos, Ordering::SeqCst);
+        tail_lock.advance(pos);
 
-            self.shared.ring[seat].num_reads.store(0, Ordering::SeqCst);
-            self.shared.tail.store(tail, Ordering::SeqCst);
-            tail_lock.0 = tail;
-            return Ok(());
+        // a parked sender was woken for a free seat and just used it.
+        // if there's still room, the next one in line gets a turn.
+        #[cfg(feature = "std")]
+        let pass_on = blocker.parked() && self.shared.has_room(&tail_lock, 1);
+        #[cfg(not(feature = "std"))]
+        let _ = blocker;
+        drop(tail_lock);
+
+        self.shared.notify_receivers();
+        #[cfg(feature = "std")]
+        if pass_on {
+            self.shared.seat_freed();
         }
     }
-    /// Sends a message. Will loop if the channel is full.
+
+    /// Sends a message. Waits for the slowest receiver if the channel is full.
+    ///
+    /// How the thread waits depends on the channel's [`WaitStrategy`]. By default
+    /// with the `std` feature, the thread is parked until a receiver frees up a seat,
+    /// and parked senders are woken one at a time, in the order they started waiting.
+    /// Without it, this spins.
     ///
     /// # Errors
     /// - if there are no readers to receive the message.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(1);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let producers: Vec<_> = (0..4)
+    ///     .map(|i| {
+    ///         let tx = tx.clone();
+    ///         std::thread::spawn(move || tx.blocking_send(i).unwrap())
+    ///     })
+    ///     .collect();
+    ///
+    /// let mut received: Vec<_> = (0..4).map(|_| rx.recv().unwrap()).collect();
+    /// received.sort();
+    /// assert_eq!(received, vec![0, 1, 2, 3]);
+    ///
+    /// for producer in producers {
+    ///     producer.join().unwrap();
+    /// }
+    /// ```
     pub fn blocking_send(&self, value: T) -> Result<(), BlockingSendError<T>> {
-        self.send_inner(value, true).map_err(|e| match e {
-            SendError::Disconnected(val) => BlockingSendError::Disconnected(val),
-            _ => unreachable!(),
-        })
+        self.send_inner(value, SendCondition::Block)
+            .map_err(|e| match e {
+                InnerSendError::Disconnected(val) => BlockingSendError::Disconnected(val),
+                _ => unreachable!(),
+            })
     }
 
     /// Sends a message.
@@ -104,11 +449,358 @@ impl<T: Clone> Channel<T> {
     /// - if there are no readers to receive the message.
     /// - if the channel is full.
     pub fn send(&self, value: T) -> Result<(), SendError<T>> {
-        self.send_inner(value, false)
+        self.send_inner(value, SendCondition::Try)
+            .map_err(|e| match e {
+                InnerSendError::Disconnected(val) => SendError::Disconnected(val),
+                InnerSendError::Full(val) => SendError::Full(val),
+                #[cfg(feature = "std")]
+                InnerSendError::Timeout(_) => unreachable!(),
+            })
+    }
+
+    /// Sends every message in `values` as one uninterrupted run. Waits until the channel
+    /// has room for all of them.
+    ///
+    /// Receivers never see messages from another sender in between, and never see part
+    /// of the batch without the rest.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the messages.
+    /// - if the batch is larger than the channel's capacity, so it would never fit.
+    ///   With [`Overflow::Grow`] the channel grows to fit it instead.
+    ///
+    /// Either way, the messages are handed back.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let producers: Vec<_> = (0..2)
+    ///     .map(|p| {
+    ///         let tx = tx.clone();
+    ///         std::thread::spawn(move || {
+    ///             for batch in 0..10 {
+    ///                 tx.send_batch((0..3).map(|i| (p, batch, i))).unwrap();
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    ///
+    /// for _ in 0..20 {
+    ///     let first = rx.recv().unwrap();
+    ///     assert_eq!(first.2, 0);
+    ///     // the rest of the batch follows, from the same producer
+    ///     assert_eq!(rx.recv(), Ok((first.0, first.1, 1)));
+    ///     assert_eq!(rx.recv(), Ok((first.0, first.1, 2)));
+    /// }
+    ///
+    /// for producer in producers {
+    ///     producer.join().unwrap();
+    /// }
+    ///
+    /// let too_big: Vec<_> = (0..5).map(|i| (0, 0, i)).collect();
+    /// assert_eq!(tx.send_batch(too_big.clone()), Err(SendError::Full(too_big)));
+    /// ```
+    pub fn send_batch(&self, values: impl IntoIterator<Item = T>) -> Result<(), SendError<Vec<T>>> {
+        self.send_batch_inner(values.into_iter().collect(), SendCondition::Block)
+            .map_err(|e| match e {
+                InnerSendError::Disconnected(vals) => SendError::Disconnected(vals),
+                InnerSendError::Full(vals) => SendError::Full(vals),
+                #[cfg(feature = "std")]
+                InnerSendError::Timeout(_) => unreachable!(),
+            })
+    }
+
+    /// Sends every message in `values` as one uninterrupted run, if the channel has room
+    /// for all of them right now. See [`Channel::send_batch`].
+    ///
+    /// Nothing is sent unless everything is.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the messages.
+    /// - if the channel doesn't have room for the whole batch.
+    ///
+    /// Either way, the messages are handed back.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(0).unwrap();
+    ///
+    /// assert_eq!(tx.try_send_batch([1, 2, 3, 4]), Err(SendError::Full(vec![1, 2, 3, 4])));
+    /// tx.try_send_batch([1, 2, 3]).unwrap();
+    ///
+    /// let mut received = vec![];
+    /// rx.try_recv_many(&mut received, 8).unwrap();
+    /// assert_eq!(received, vec![0, 1, 2, 3]);
+    /// ```
+    pub fn try_send_batch(
+        &self,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<(), SendError<Vec<T>>> {
+        self.send_batch_inner(values.into_iter().collect(), SendCondition::Try)
+            .map_err(|e| match e {
+                InnerSendError::Disconnected(vals) => SendError::Disconnected(vals),
+                InnerSendError::Full(vals) => SendError::Full(vals),
+                #[cfg(feature = "std")]
+                InnerSendError::Timeout(_) => unreachable!(),
+            })
+    }
+
+    /// Sends a message asynchronously. Waits for the slowest receiver if the channel is full.
+    ///
+    /// This doesn't depend on any runtime. The task is woken once a receiver frees up
+    /// a seat, in the order tasks and threads started waiting.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the message.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    /// # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    /// #     struct Unparker(std::thread::Thread);
+    /// #     impl std::task::Wake for Unparker {
+    /// #         fn wake(self: std::sync::Arc<Self>) { self.0.unpark() }
+    /// #     }
+    /// #     let waker = std::sync::Arc::new(Unparker(std::thread::current())).into();
+    /// #     let mut cx = std::task::Context::from_waker(&waker);
+    /// #     let mut fut = std::pin::pin!(fut);
+    /// #     loop {
+    /// #         match fut.as_mut().poll(&mut cx) {
+    /// #             std::task::Poll::Ready(out) => return out,
+    /// #             std::task::Poll::Pending => std::thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// let tx = Channel::new(1);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let consumer = std::thread::spawn(move || {
+    ///     (0..3).map(|_| rx.recv().unwrap()).collect::<Vec<_>>()
+    /// });
+    ///
+    /// block_on(async {
+    ///     for i in 0..3 {
+    ///         tx.send_async(i).await.unwrap();
+    ///     }
+    /// });
+    /// assert_eq!(consumer.join().unwrap(), vec![0, 1, 2]);
+    /// ```
+    pub fn send_async(&self, value: T) -> SendFuture<'_, T> {
+        SendFuture {
+            channel: self,
+            value: Some(value),
+            waker: None,
+        }
+    }
+
+    /// Tries to send `value`, registering the task to be woken when the channel has room.
+    ///
+    /// `value` is taken once it's sent, or handed back in the error. `waker` holds the
+    /// task's place in line between polls.
+    pub(crate) fn poll_send(
+        &self,
+        value: &mut Option<T>,
+        waker: &mut Option<usize>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), BlockingSendError<T>>> {
+        let mut registered = false;
+        loop {
+            let val = value.take().expect("polled after completion");
+            // only the first try counts toward evicting
+            let cond = if waker.is_none() && !registered {
+                SendCondition::Try
+            } else {
+                SendCondition::Retry
+            };
+            match self.send_inner(val, cond) {
+                Ok(()) => {
+                    // we were let through for a free seat. if there's still room,
+                    // the next one in line gets a turn.
+                    if self.shared.tx_waiters.unregister(waker)
+                        && self.shared.has_room(&self.shared.lock_tail(), 1)
+                    {
+                        self.shared.seat_freed();
+                    }
+                    return Poll::Ready(Ok(()));
+                }
+                Err(InnerSendError::Disconnected(val)) => {
+                    self.shared.tx_waiters.unregister(waker);
+                    return Poll::Ready(Err(BlockingSendError::Disconnected(val)));
+                }
+                Err(InnerSendError::Full(val)) => {
+                    *value = Some(val);
+                    if registered {
+                        // there's no alarm for a task to set, so the channel sets one
+                        #[cfg(feature = "std")]
+                        self.shared.arm_eviction_alarm();
+                        return Poll::Pending;
+                    }
+                    // check once more after registering, a seat might have been freed in between
+                    self.shared.tx_waiters.register(waker, cx.waker());
+                    registered = true;
+                }
+                #[cfg(feature = "std")]
+                Err(InnerSendError::Timeout(_)) => unreachable!(),
+            }
+        }
+    }
+
+    /// Gives up a place in line taken by [`Channel::poll_send`].
+    pub(crate) fn cancel_send(&self, waker: &mut Option<usize>) {
+        if self.shared.tx_waiters.unregister(waker) {
+            // we were woken for a free seat we'll never use
+            self.shared.seat_freed();
+        }
+    }
+
+    /// Sends a message, waiting at most `timeout` for the channel to have room.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the message.
+    /// - if the channel was still full when the time ran out.
+    ///
+    /// Either way, the message is handed back.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(1);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// tx.send_timeout(1, Duration::from_millis(10)).unwrap();
+    /// // rx hasn't read yet
+    /// assert_eq!(
+    ///     tx.send_timeout(2, Duration::from_millis(10)),
+    ///     Err(SendTimeoutError::Timeout(2))
+    /// );
+    ///
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// tx.send_timeout(2, Duration::from_millis(10)).unwrap();
+    ///
+    /// drop(rx);
+    /// assert_eq!(
+    ///     tx.send_timeout(3, Duration::from_millis(10)),
+    ///     Err(SendTimeoutError::Disconnected(3))
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn send_timeout(&self, value: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.send_deadline(value, deadline),
+            // too far in the future to represent. that's forever.
+            None => self.blocking_send(value).map_err(|e| match e {
+                BlockingSendError::Disconnected(val) => SendTimeoutError::Disconnected(val),
+            }),
+        }
+    }
+
+    /// Sends a message, waiting until `deadline` for the channel to have room.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the message.
+    /// - if the channel was still full at the deadline.
+    ///
+    /// Either way, the message is handed back.
+    #[cfg(feature = "std")]
+    pub fn send_deadline(&self, value: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        self.send_inner(value, SendCondition::Deadline(deadline))
+            .map_err(|e| match e {
+                InnerSendError::Disconnected(val) => SendTimeoutError::Disconnected(val),
+                InnerSendError::Timeout(val) => SendTimeoutError::Timeout(val),
+                _ => unreachable!(),
+            })
     }
 }
 
-impl<T: Clone> Clone for Channel<T> {
+/// For messages that aren't [`Clone`]. Each one is stored once, and every receiver gets
+/// an [`Arc`] to it.
+impl<T: Send + Sync> Channel<Arc<T>> {
+    /// Create a new channel for messages that aren't [`Clone`], like handles or file
+    /// descriptors. Send them with [`Channel::send_shared`], and every receiver gets an
+    /// [`Arc`] to the same one.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use trotcast::prelude::*;
+    ///
+    /// // not `Clone`
+    /// struct Handle(u32);
+    ///
+    /// let tx = Channel::shared(4);
+    /// let mut rx1 = tx.spawn_rx();
+    /// let mut rx2 = tx.spawn_rx();
+    /// tx.send_shared(Handle(7)).unwrap();
+    ///
+    /// let first: Arc<Handle> = rx1.recv().unwrap();
+    /// let second = rx2.recv().unwrap();
+    /// assert_eq!(first.0, 7);
+    /// assert!(Arc::ptr_eq(&first, &second));
+    /// ```
+    pub fn shared(capacity: usize) -> Self {
+        Self::new(capacity)
+    }
+
+    /// Sends a message, shared by every receiver.
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the message.
+    /// - if the channel is full.
+    pub fn send_shared(&self, value: T) -> Result<(), SendError<T>> {
+        self.send(Arc::new(value)).map_err(|e| match e {
+            SendError::Disconnected(val) => SendError::Disconnected(unshare(val)),
+            SendError::Full(val) => SendError::Full(unshare(val)),
+        })
+    }
+
+    /// Sends a message, shared by every receiver. Waits for the slowest receiver if the
+    /// channel is full, like [`Channel::blocking_send`].
+    ///
+    /// # Errors
+    /// - if there are no readers to receive the message.
+    pub fn blocking_send_shared(&self, value: T) -> Result<(), BlockingSendError<T>> {
+        self.blocking_send(Arc::new(value)).map_err(|e| match e {
+            BlockingSendError::Disconnected(val) => BlockingSendError::Disconnected(unshare(val)),
+        })
+    }
+}
+
+/// Takes back a message that was never sent, so nobody else shares it.
+fn unshare<T>(val: Arc<T>) -> T {
+    Arc::into_inner(val).expect("unsent message is shared")
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum SendCondition {
+    Try,
+    /// like `Try`, for a send that already counted toward evicting.
+    Retry,
+    Block,
+    #[cfg(feature = "std")]
+    Deadline(Instant),
+}
+
+impl SendCondition {
+    fn deadline(self) -> Option<Deadline> {
+        match self {
+            #[cfg(feature = "std")]
+            SendCondition::Deadline(deadline) => Some(deadline),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Clone for Channel<T> {
     fn clone(&self) -> Self {
         Self::from_shared_state(Arc::clone(&self.shared))
     }
@@ -116,6 +808,9 @@ impl<T: Clone> Clone for Channel<T> {
 
 impl<T> Drop for Channel<T> {
     fn drop(&mut self) {
-        self.shared.num_writers.fetch_sub(1, Ordering::Release);
+        // the last channel closes the broadcast. wake receivers so they can see it.
+        if self.shared.num_writers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.notify_receivers();
+        }
     }
 }
@@ -17,10 +17,72 @@ impl<T> Debug<T> {
             self.shared.tail.load(Ordering::Relaxed),
             self.shared.num_readers.load(Ordering::Relaxed)
         ));
-        for (i, ring) in self.shared.ring.iter().enumerate() {
+        #[cfg(feature = "std")]
+        str.push_str(&format!(
+            "parked receivers: {}\nparked senders: {}\n",
+            self.parked_receivers(),
+            self.parked_senders()
+        ));
+        for (i, ring) in self.shared.lock_tail().ring.seats().iter().enumerate() {
             str.push_str(&format!("Seat({i}): {ring:?}\n"));
             //todo
         }
         str
     }
+
+    /// The number of receivers currently parked or registered as a task, waiting for a message.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let dbg = tx.debugger();
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let handle = std::thread::spawn(move || rx.recv());
+    ///
+    /// // an idle receiver sleeps instead of spinning
+    /// while dbg.parked_receivers() == 0 {
+    ///     std::thread::yield_now();
+    /// }
+    /// std::thread::sleep(std::time::Duration::from_millis(50));
+    /// assert_eq!(dbg.parked_receivers(), 1);
+    ///
+    /// tx.send(7).unwrap();
+    /// assert_eq!(handle.join().unwrap(), Ok(7));
+    /// assert_eq!(dbg.parked_receivers(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parked_receivers(&self) -> usize {
+        self.shared.rx_waiters.len()
+    }
+
+    /// The number of senders currently parked or registered as a task, waiting for room in the ring.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(1);
+    /// let dbg = tx.debugger();
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    ///
+    /// // both producers wait on the same seat, and are let through in order
+    /// for (i, val) in [2, 3].into_iter().enumerate() {
+    ///     let tx = tx.clone();
+    ///     std::thread::spawn(move || tx.blocking_send(val).unwrap());
+    ///     while dbg.parked_senders() != i + 1 {
+    ///         std::thread::yield_now();
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// assert_eq!(rx.recv(), Ok(2));
+    /// assert_eq!(rx.recv(), Ok(3));
+    /// assert_eq!(dbg.parked_senders(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parked_senders(&self) -> usize {
+        self.shared.tx_waiters.len()
+    }
 }
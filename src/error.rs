@@ -4,12 +4,18 @@ use core::{error::Error, fmt};
 pub enum TryRecvError {
     Empty,
     Disconnected,
+    /// The receiver fell behind and missed this many messages.
+    Lagged(u64),
+    /// The receiver held up the channel for too long, and was detached from it.
+    Evicted,
 }
 impl fmt::Display for TryRecvError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TryRecvError::Disconnected => write!(f, "Channel Disconnected"),
             TryRecvError::Empty => write!(f, "Channel Empty"),
+            TryRecvError::Lagged(missed) => write!(f, "Receiver lagged by {missed} messages"),
+            TryRecvError::Evicted => write!(f, "Receiver evicted"),
         }
     }
 }
@@ -19,17 +25,93 @@ impl Error for TryRecvError {}
 #[derive(Debug, Clone, PartialEq)]
 pub enum RecvError {
     Disconnected,
+    /// The receiver fell behind and missed this many messages.
+    Lagged(u64),
+    /// The receiver held up the channel for too long, and was detached from it.
+    Evicted,
 }
 
 impl fmt::Display for RecvError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             RecvError::Disconnected => write!(f, "Channel Disconnected"),
+            RecvError::Lagged(missed) => write!(f, "Receiver lagged by {missed} messages"),
+            RecvError::Evicted => write!(f, "Receiver evicted"),
         }
     }
 }
 impl Error for RecvError {}
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+    /// The receiver fell behind and missed this many messages.
+    Lagged(u64),
+    /// The receiver held up the channel for too long, and was detached from it.
+    Evicted,
+}
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "Timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => write!(f, "Channel Disconnected"),
+            RecvTimeoutError::Lagged(missed) => write!(f, "Receiver lagged by {missed} messages"),
+            RecvTimeoutError::Evicted => write!(f, "Receiver evicted"),
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrySelectError {
+    Empty,
+}
+
+impl fmt::Display for TrySelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySelectError::Empty => write!(f, "No receiver ready"),
+        }
+    }
+}
+
+impl Error for TrySelectError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectTimeoutError {
+    Timeout,
+}
+
+impl fmt::Display for SelectTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectTimeoutError::Timeout => write!(f, "Timed out waiting on receivers"),
+        }
+    }
+}
+
+impl Error for SelectTimeoutError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResizeError {
+    /// A receiver still has this many messages to read, more than the new capacity.
+    Unread(usize),
+}
+
+impl fmt::Display for ResizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResizeError::Unread(unread) => {
+                write!(f, "{unread} unread messages don't fit the new capacity")
+            }
+        }
+    }
+}
+
+impl Error for ResizeError {}
+
 #[derive(Clone, PartialEq)]
 pub enum SendError<T> {
     Disconnected(T),
@@ -80,7 +162,57 @@ impl<T> fmt::Display for BlockingSendError<T> {
 
 impl<T> Error for BlockingSendError<T> {}
 
+#[derive(Clone, PartialEq)]
+pub enum SendTimeoutError<T> {
+    Timeout(T),
+    Disconnected(T),
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.debug_struct("SendTimeoutError::Timeout").finish(),
+            SendTimeoutError::Disconnected(_) => {
+                f.debug_struct("SendTimeoutError::Disconnected").finish()
+            }
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => write!(f, "Timed out waiting on channel"),
+            SendTimeoutError::Disconnected(_) => write!(f, "Channel Disconnected"),
+        }
+    }
+}
+
+impl<T> Error for SendTimeoutError<T> {}
+
+pub(crate) enum InnerSendError<T> {
+    Disconnected(T),
+    Full(T),
+    #[cfg(feature = "std")]
+    Timeout(T),
+}
+
+impl InnerSendError<()> {
+    /// Hands `value` back with the error.
+    pub(crate) fn with<T>(self, value: T) -> InnerSendError<T> {
+        match self {
+            InnerSendError::Disconnected(()) => InnerSendError::Disconnected(value),
+            InnerSendError::Full(()) => InnerSendError::Full(value),
+            #[cfg(feature = "std")]
+            InnerSendError::Timeout(()) => InnerSendError::Timeout(value),
+        }
+    }
+}
+
 pub enum InnerRecvError {
     Disconnected,
     Empty,
+    Timeout,
+    Lagged(u64),
+    Evicted,
 }
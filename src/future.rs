@@ -0,0 +1,229 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::prelude::*;
+
+/// Future returned by [`Receiver::recv_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvFuture<'a, T> {
+    pub(crate) receiver: &'a mut Receiver<T>,
+}
+
+impl<T: Clone> Future for RecvFuture<'_, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for RecvFuture<'_, T> {
+    fn drop(&mut self) {
+        // cancelled or done. either way, nobody needs waking anymore.
+        let receiver = &mut *self.receiver;
+        receiver.shared.rx_waiters.unregister(&mut receiver.waker);
+    }
+}
+
+/// Future returned by [`Channel::send_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendFuture<'a, T> {
+    pub(crate) channel: &'a Channel<T>,
+    pub(crate) value: Option<T>,
+    pub(crate) waker: Option<usize>,
+}
+
+impl<T> Unpin for SendFuture<'_, T> {}
+
+impl<T> Future for SendFuture<'_, T> {
+    type Output = Result<(), BlockingSendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.channel.poll_send(&mut this.value, &mut this.waker, cx)
+    }
+}
+
+impl<T> Drop for SendFuture<'_, T> {
+    fn drop(&mut self) {
+        self.channel.cancel_send(&mut self.waker);
+    }
+}
+
+/// Yields every message until the channel is closed, or the receiver is evicted.
+///
+/// With [`Overflow::Lag`], messages the receiver fell too far behind on are skipped.
+///
+/// Enabled with the `stream` feature.
+///
+/// ```
+/// use futures_core::Stream;
+/// use trotcast::prelude::*;
+/// # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+/// #     struct Unparker(std::thread::Thread);
+/// #     impl std::task::Wake for Unparker {
+/// #         fn wake(self: std::sync::Arc<Self>) { self.0.unpark() }
+/// #     }
+/// #     let waker = std::sync::Arc::new(Unparker(std::thread::current())).into();
+/// #     let mut cx = std::task::Context::from_waker(&waker);
+/// #     let mut fut = std::pin::pin!(fut);
+/// #     loop {
+/// #         match fut.as_mut().poll(&mut cx) {
+/// #             std::task::Poll::Ready(out) => return out,
+/// #             std::task::Poll::Pending => std::thread::park(),
+/// #         }
+/// #     }
+/// # }
+///
+/// let tx = Channel::new(4);
+/// let mut rx = tx.spawn_rx();
+/// tx.send(1).unwrap();
+/// tx.send(2).unwrap();
+/// drop(tx);
+///
+/// let mut received = vec![];
+/// block_on(std::future::poll_fn(|cx| {
+///     while let std::task::Poll::Ready(msg) = std::pin::Pin::new(&mut rx).poll_next(cx) {
+///         match msg {
+///             Some(msg) => received.push(msg),
+///             None => return std::task::Poll::Ready(()),
+///         }
+///     }
+///     std::task::Poll::Pending
+/// }));
+/// assert_eq!(received, vec![1, 2]);
+/// ```
+#[cfg(feature = "stream")]
+impl<T: Clone> futures_core::Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        loop {
+            match this.poll_recv(cx) {
+                // missed messages are gone. carry on from the oldest one left.
+                Poll::Ready(Err(RecvError::Lagged(_))) => continue,
+                poll => return poll.map(Result::ok),
+            }
+        }
+    }
+}
+
+/// A [`Sink`](futures_sink::Sink) that sends into a [`Channel`].
+///
+/// Sending waits for room in the channel without spinning, the same way
+/// [`Channel::send_async`] does. Holds at most one message that hasn't made
+/// it into the channel yet, which `poll_ready` and `poll_flush` push through.
+///
+/// Enabled with the `sink` feature.
+///
+/// ```
+/// use futures_sink::Sink;
+/// use trotcast::prelude::*;
+/// # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+/// #     struct Unparker(std::thread::Thread);
+/// #     impl std::task::Wake for Unparker {
+/// #         fn wake(self: std::sync::Arc<Self>) { self.0.unpark() }
+/// #     }
+/// #     let waker = std::sync::Arc::new(Unparker(std::thread::current())).into();
+/// #     let mut cx = std::task::Context::from_waker(&waker);
+/// #     let mut fut = std::pin::pin!(fut);
+/// #     loop {
+/// #         match fut.as_mut().poll(&mut cx) {
+/// #             std::task::Poll::Ready(out) => return out,
+/// #             std::task::Poll::Pending => std::thread::park(),
+/// #         }
+/// #     }
+/// # }
+///
+/// let tx = Channel::new(1);
+/// let mut rx = tx.spawn_rx();
+/// let mut sink = std::pin::pin!(ChannelSink::new(tx));
+///
+/// let consumer = std::thread::spawn(move || {
+///     (0..3).map(|_| rx.recv().unwrap()).collect::<Vec<_>>()
+/// });
+///
+/// let mut next = 0;
+/// block_on(std::future::poll_fn(|cx| {
+///     while next < 3 {
+///         std::task::ready!(sink.as_mut().poll_ready(cx)).unwrap();
+///         sink.as_mut().start_send(next).unwrap();
+///         next += 1;
+///     }
+///     sink.as_mut().poll_flush(cx)
+/// }))
+/// .unwrap();
+/// assert_eq!(consumer.join().unwrap(), vec![0, 1, 2]);
+/// ```
+#[cfg(feature = "sink")]
+pub struct ChannelSink<T> {
+    channel: Channel<T>,
+    pending: Option<T>,
+    waker: Option<usize>,
+}
+
+#[cfg(feature = "sink")]
+impl<T> ChannelSink<T> {
+    /// Wraps `channel` in a sink.
+    pub fn new(channel: Channel<T>) -> Self {
+        Self {
+            channel,
+            pending: None,
+            waker: None,
+        }
+    }
+
+    /// The channel this sink sends into.
+    pub fn get_ref(&self) -> &Channel<T> {
+        &self.channel
+    }
+
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BlockingSendError<T>>> {
+        if self.pending.is_none() {
+            return Poll::Ready(Ok(()));
+        }
+        self.channel
+            .poll_send(&mut self.pending, &mut self.waker, cx)
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<T> Unpin for ChannelSink<T> {}
+
+#[cfg(feature = "sink")]
+impl<T> futures_sink::Sink<T> for ChannelSink<T> {
+    type Error = BlockingSendError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        assert!(
+            this.pending.is_none(),
+            "start_send called without poll_ready"
+        );
+        this.pending = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending(cx)
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<T> Drop for ChannelSink<T> {
+    fn drop(&mut self) {
+        self.channel.cancel_send(&mut self.waker);
+    }
+}
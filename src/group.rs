@@ -0,0 +1,193 @@
+use alloc::{
+    collections::VecDeque,
+    string::String,
+    sync::{Arc, Weak},
+};
+
+use crate::{
+    mutex::{Mutex, lock},
+    prelude::*,
+};
+
+/// A member of a consumer group, from [`Channel::join_group`].
+///
+/// Every group gets every message, like a single receiver would, and counts as one
+/// toward holding up the channel. Within a group, each message goes to just one member.
+///
+/// A member works on one message at a time. It's done with it once it asks for the
+/// next one, or calls [`GroupReceiver::done`]. If it's dropped before that, the
+/// message is handed to another member of the group.
+///
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let tx = Channel::new(16);
+/// let mut audit = tx.join_group("audit");
+/// let workers: Vec<_> = (0..3).map(|_| tx.join_group("billing")).collect();
+///
+/// for order in 0..9 {
+///     tx.send(order).unwrap();
+/// }
+/// drop(tx);
+///
+/// let handles: Vec<_> = workers
+///     .into_iter()
+///     .map(|mut worker| {
+///         std::thread::spawn(move || {
+///             let mut billed = vec![];
+///             while let Ok(&order) = worker.recv() {
+///                 billed.push(order);
+///             }
+///             billed
+///         })
+///     })
+///     .collect();
+///
+/// // each order is billed once, by one of the workers
+/// let mut billed: Vec<_> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+/// billed.sort();
+/// assert_eq!(billed, (0..9).collect::<Vec<_>>());
+///
+/// // and audited once too
+/// assert_eq!(audit.recv(), Ok(&0));
+/// ```
+pub struct GroupReceiver<T> {
+    group: Arc<GroupState<T>>,
+    /// the message this member is working on.
+    claimed: Option<T>,
+}
+
+/// What the members of a group share.
+pub(crate) struct GroupState<T> {
+    name: String,
+    /// reads for the whole group.
+    rx: Mutex<Receiver<T>>,
+    /// messages members left unfinished, for the others to pick up first.
+    returned: Mutex<VecDeque<T>>,
+}
+
+impl<T> GroupReceiver<T> {
+    /// Joins the group called `name` on the channel behind `shared`, creating it at the
+    /// tail if it has no members.
+    pub(crate) fn join(shared: &Arc<State<T>>, name: &str) -> Self {
+        let mut groups = lock(&shared.groups);
+        groups.retain(|group| group.strong_count() > 0);
+        let group = match groups
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|group| group.name == name)
+        {
+            Some(group) => group,
+            None => {
+                let group = Arc::new(GroupState {
+                    name: name.into(),
+                    rx: Mutex::new(Receiver::new(Arc::clone(shared))),
+                    returned: Mutex::new(VecDeque::new()),
+                });
+                groups.push(Arc::downgrade(&group));
+                group
+            }
+        };
+        Self {
+            group,
+            claimed: None,
+        }
+    }
+
+    /// The name of the group.
+    pub fn group(&self) -> &str {
+        &self.group.name
+    }
+
+    /// Finishes the message this member is working on.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut first = tx.join_group("workers");
+    /// let mut second = tx.join_group("workers");
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    ///
+    /// assert_eq!(first.try_recv(), Ok(&1));
+    /// // `first` goes away before it's done, so `second` gets it
+    /// drop(first);
+    /// assert_eq!(second.try_recv(), Ok(&1));
+    /// assert_eq!(second.try_recv(), Ok(&2));
+    /// second.done();
+    /// ```
+    pub fn done(&mut self) {
+        self.claimed = None;
+    }
+
+    /// Rejoins the channel after the group was evicted, starting at the next message
+    /// sent. Does nothing if it wasn't evicted.
+    pub fn resubscribe(&mut self) {
+        lock(&self.group.rx).resubscribe();
+    }
+
+    fn claim(&mut self, msg: T) -> &T {
+        self.claimed.insert(msg)
+    }
+}
+
+impl<T: Clone> GroupReceiver<T> {
+    /// Try to claim the next message for this group, finishing the one before.
+    ///
+    /// # Errors
+    /// - if there's no new message available
+    /// - if the channel is closed
+    /// - if the group fell behind an [`Overflow::Lag`] channel
+    /// - if the group was evicted from an [`Overflow::Evict`] channel
+    pub fn try_recv(&mut self) -> Result<&T, TryRecvError> {
+        self.done();
+        let returned = lock(&self.group.returned).pop_front();
+        if let Some(msg) = returned {
+            return Ok(self.claim(msg));
+        }
+        let msg = lock(&self.group.rx).try_recv()?;
+        Ok(self.claim(msg))
+    }
+
+    /// Claim the next message for this group, finishing the one before. Blocks until
+    /// a message is available.
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if the group fell behind an [`Overflow::Lag`] channel
+    /// - if the group was evicted from an [`Overflow::Evict`] channel
+    pub fn recv(&mut self) -> Result<&T, RecvError> {
+        self.done();
+        let shared = Arc::clone(&lock(&self.group.rx).shared);
+        let mut blocker = shared.rx_blocker(None);
+        loop {
+            let returned = lock(&self.group.returned).pop_front();
+            if let Some(msg) = returned {
+                return Ok(self.claim(msg));
+            }
+            // don't hold the receiver while waiting, other members check for returns
+            let next = lock(&self.group.rx).try_recv();
+            match next {
+                Ok(msg) => return Ok(self.claim(msg)),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => return Err(RecvError::Disconnected),
+                Err(TryRecvError::Lagged(missed)) => return Err(RecvError::Lagged(missed)),
+                Err(TryRecvError::Evicted) => return Err(RecvError::Evicted),
+            }
+            blocker
+                .wait(|| !lock(&self.group.returned).is_empty() || lock(&self.group.rx).is_ready());
+        }
+    }
+}
+
+impl<T> Drop for GroupReceiver<T> {
+    fn drop(&mut self) {
+        let Some(msg) = self.claimed.take() else {
+            return;
+        };
+        // hand it to someone else. it's older than anything still in the channel.
+        lock(&self.group.returned).push_front(msg);
+        lock(&self.group.rx).shared.notify_receivers();
+    }
+}
@@ -28,10 +28,81 @@ from all receivers, then your channel will be blocked on the non-reading receive
 However, other receivers will be able to receiver prior messages until reaching
 the state of the non-reading receiver.
 
+If a slow receiver should never hold up the channel, build it with [`Overflow::Lag`].
+Senders then overwrite the oldest message, and a receiver that falls behind is told
+how many messages it missed.
+With [`Overflow::Evict`], a receiver that holds up the channel for too long is
+detached instead, and told so on its next receive.
+If memory is cheaper than waiting, [`Channel::unbounded`] grows instead.
+A receiver that only wants some messages can say so with [`Channel::spawn_rx_interested`],
+and isn't waited on for the rest. A [`TopicBus`] does this by topic, with wildcards.
+To share the work of reading among several workers, have them join a group with
+[`Channel::join_group`].
+
+For state where only the newest value matters, use a [`Watch`].
+
 
 You can clone receivers. If you need another `Channel`, you can call `Receiver::clone_channel`.
 
-Recievers will not lock any `Mutex` or `RwLock`.
+Receivers will not lock any `Mutex` while messages are available. With [`Overflow::Lag`],
+each read takes a read lock on its seat, so a sender can't write over a message
+while it's being read.
+With the `std` feature, an idle receiver blocked in `Receiver::recv` parks its
+thread instead of spinning, and is woken by the next send.
+
+## Waiting
+
+Blocked senders and receivers wait according to the channel's [`WaitStrategy`](strategy::WaitStrategy),
+picked with [`Channel::with_wait_strategy`]. See the [`strategy`] module for the built-in ones.
+
+## Messages that aren't `Clone`
+
+Every receiver gets every message, so [`Receiver::recv`] clones it for all but the last
+one. Messages that can't or shouldn't be cloned are stored once, and read in place with
+[`Receiver::recv_ref`] or [`Receiver::recv_with`]. To hand every receiver a handle of its
+own instead, create the channel with [`Channel::shared`]. Receivers then get an `Arc<T>`
+to the one message sent.
+
+```
+use std::sync::Arc;
+use trotcast::prelude::*;
+
+// not `Clone`
+struct Frame {
+    id: u32,
+    pixels: Vec<u8>,
+}
+
+let tx = Channel::new(4);
+let receivers: Vec<_> = (0..2).map(|_| tx.spawn_rx()).collect();
+tx.send(Frame { id: 1, pixels: vec![0; 64] }).unwrap();
+
+let handles: Vec<_> = receivers
+    .into_iter()
+    .map(|mut rx| std::thread::spawn(move || rx.recv_with(|frame| (frame.id, frame.pixels.len()))))
+    .collect();
+for handle in handles {
+    assert_eq!(handle.join().unwrap(), Ok((1, 64)));
+}
+
+let tx = Channel::shared(4);
+let mut rx = tx.spawn_rx();
+tx.send_shared(Frame { id: 2, pixels: vec![] }).unwrap();
+let frame: Arc<Frame> = rx.recv().unwrap();
+assert_eq!(frame.id, 2);
+```
+
+Messages are shared between threads, so they have to be `Send + Sync`.
+
+```compile_fail
+use std::rc::Rc;
+use trotcast::prelude::*;
+
+let tx = Channel::new(4);
+let mut rx = tx.spawn_rx();
+tx.send(Rc::new(1)).unwrap();
+std::thread::spawn(move || rx.recv_with(|msg| **msg));
+```
 
 # Example
 
@@ -129,19 +200,52 @@ pub use receiver::*;
 mod channel;
 pub use channel::*;
 
+mod builder;
+pub use builder::*;
+
+mod future;
+pub use future::*;
+
+mod select;
+pub use select::*;
+
+mod watch;
+pub use watch::*;
+
+mod topic;
+pub use topic::*;
+
+mod group;
+pub use group::*;
+
+pub(crate) mod ring;
+
 pub(crate) mod seat;
 
 pub(crate) mod state;
 
+pub(crate) mod wait;
+
+pub mod strategy;
+
 #[cfg(feature = "debug")]
 pub mod debug;
 
 pub mod prelude {
+    pub use crate::builder::*;
     pub use crate::channel::*;
     pub use crate::error::*;
+    pub use crate::future::*;
+    pub use crate::group::*;
     pub use crate::receiver::*;
+    pub(crate) use crate::ring::*;
     pub(crate) use crate::seat::*;
+    pub use crate::select::*;
     pub(crate) use crate::state::*;
+    pub use crate::strategy::*;
+    pub use crate::topic::*;
+    pub(crate) use crate::wait::*;
+    pub use crate::watch::*;
 
     #[cfg(feature = "debug")]
     pub use crate::debug::*;
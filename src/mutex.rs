@@ -1,4 +1,22 @@
 #[cfg(not(feature = "std"))]
-pub use spin::Mutex;
+pub use spin::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 #[cfg(feature = "std")]
-pub use std::sync::Mutex;
+pub use std::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Locks `mutex`, whichever kind it is.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> impl core::ops::DerefMut<Target = T> + '_ {
+    #[cfg(feature = "std")]
+    return mutex.lock().unwrap();
+
+    #[cfg(not(feature = "std"))]
+    return mutex.lock();
+}
+
+/// Takes a read lock on `rwlock`, whichever kind it is.
+pub(crate) fn read<T>(rwlock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    #[cfg(feature = "std")]
+    return rwlock.read().unwrap_or_else(|e| e.into_inner());
+
+    #[cfg(not(feature = "std"))]
+    return rwlock.read();
+}
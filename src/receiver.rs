@@ -1,7 +1,18 @@
-use core::sync::atomic::Ordering;
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
 
-use crate::prelude::*;
-use alloc::sync::Arc;
+use crate::{mutex::RwLockReadGuard, prelude::*};
+use alloc::{
+    sync::Arc,
+    vec::{self, Vec},
+};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 /// A receiver handle for the broadcast channel that allows for consuming messages.
 ///
@@ -15,16 +26,105 @@ pub struct Receiver<T> {
     pub head: usize,
     #[cfg(not(feature = "debug"))]
     pub(crate) head: usize,
+    /// registration of the last task that polled this receiver
+    pub(crate) waker: Option<usize>,
+    /// where senders see this receiver
+    pub(crate) cursor: Arc<Cursor>,
+    /// the ring holding the message at `head`, or the next one sent.
+    pub(crate) ring: Arc<Ring<T>>,
 }
 
-impl<T: Clone> Receiver<T> {
+impl<T> Receiver<T> {
     pub(crate) fn new(shared: Arc<State<T>>) -> Self {
-        shared.num_readers.fetch_add(1, Ordering::Release);
+        Self::join(shared, 0, Cursor::ALL, 0)
+    }
+
+    /// A receiver that starts up to `history` messages before the tail, but not before
+    /// `from`, and only gets messages with tags in `interest`.
+    pub(crate) fn join(shared: Arc<State<T>>, history: usize, interest: u64, from: usize) -> Self {
+        // join under the tail lock, so every message from `head` on counts us.
+        let (head, cursor, ring) = {
+            let mut tail = shared.lock_tail();
+            shared.num_readers.fetch_add(1, Ordering::Release);
+            // only what's kept as history is sure to still be there
+            let head = tail
+                .pos
+                .saturating_sub(history.min(tail.ring.history))
+                .max(tail.ring.start)
+                .max(from.min(tail.pos));
+            let cursor = Arc::new(Cursor::new(head, interest));
+            tail.ring.add_reader(head, tail.pos, &cursor);
+            tail.readers.push(Arc::clone(&cursor));
+            (head, cursor, Arc::clone(&tail.ring))
+        };
         Self {
-            head: shared.tail.load(Ordering::Relaxed),
+            head,
             shared,
+            waker: None,
+            cursor,
+            ring,
+        }
+    }
+    /// Creates a receiver that starts at the next message sent, like
+    /// [`Channel::spawn_rx`]. Cloning a receiver does the same.
+    pub fn subscribe(&self) -> Self {
+        Receiver::new(Arc::clone(&self.shared))
+    }
+
+    /// Creates a receiver at the same position as this one. It receives every message
+    /// this one hasn't read yet, and everything sent after. It wants the same messages
+    /// as this one, see [`Channel::spawn_rx_interested`].
+    ///
+    /// A fork of an evicted receiver is evicted too.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// assert_eq!(rx.recv(), Ok(1));
+    ///
+    /// let mut forked = rx.fork();
+    /// let mut subscribed = rx.subscribe();
+    /// tx.send(3).unwrap();
+    ///
+    /// assert_eq!(forked.recv(), Ok(2));
+    /// assert_eq!(forked.recv(), Ok(3));
+    /// assert_eq!(subscribed.recv(), Ok(3));
+    /// assert_eq!(rx.recv(), Ok(2));
+    /// assert_eq!(rx.recv(), Ok(3));
+    /// ```
+    pub fn fork(&self) -> Self {
+        let mut tail = self.shared.lock_tail();
+        let cursor = if self.cursor.is_evicted() {
+            Arc::new(Cursor::new(Cursor::EVICTED, self.cursor.interest))
+        } else {
+            // every message we haven't read yet has to wait for the fork as well
+            self.shared.num_readers.fetch_add(1, Ordering::Release);
+            let cursor = Arc::new(Cursor::new(self.head, self.cursor.interest));
+            let mut ring = Arc::clone(&self.ring);
+            loop {
+                ring.add_reader(self.head, tail.pos, &cursor);
+                if Arc::ptr_eq(&ring, &tail.ring) {
+                    break;
+                }
+                ring = ring.next();
+            }
+            tail.readers.push(Arc::clone(&cursor));
+            cursor
+        };
+        drop(tail);
+        Self {
+            head: self.head,
+            shared: Arc::clone(&self.shared),
+            waker: None,
+            cursor,
+            ring: Arc::clone(&self.ring),
         }
     }
+
     /// Clones the interior [`Channel`]
     pub fn clone_channel(&self) -> Channel<T> {
         Channel::from_shared_state(Arc::clone(&self.shared))
@@ -34,36 +134,975 @@ impl<T: Clone> Receiver<T> {
         self.shared.num_writers.load(Ordering::Relaxed) == 0
     }
 
+    /// Whether a sender detached this receiver, with [`Overflow::Evict`].
+    pub fn evicted(&self) -> bool {
+        self.cursor.is_evicted()
+    }
+
+    /// How many messages were sent before the next one this receiver reads. Only ever
+    /// goes up, and keeps counting across forks and resumes.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    /// let mut late = tx.spawn_rx();
+    /// tx.send(2).unwrap();
+    ///
+    /// assert_eq!(rx.position(), 0);
+    /// assert_eq!(late.position(), 1);
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// assert_eq!(rx.position(), 1);
+    /// ```
+    pub fn position(&self) -> usize {
+        self.head
+    }
+
+    /// How far behind the newest message this receiver is, counting messages that were
+    /// written over or that it isn't interested in.
+    ///
+    /// An evicted receiver stays where it was, so its lag keeps growing.
+    pub fn lag(&self) -> usize {
+        self.shared
+            .tail
+            .load(Ordering::Acquire)
+            .saturating_sub(self.head)
+    }
+
+    /// How many messages are left for this receiver to read. Unlike [`Receiver::lag`],
+    /// this leaves out messages an [`Overflow::Lag`] channel has written over.
+    ///
+    /// Only messages the receiver is [interested](Channel::spawn_rx_interested) in are
+    /// counted, which takes a look at each one left. An evicted receiver has nothing
+    /// left.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::builder(2).overflow(Overflow::Lag).build();
+    /// let mut rx = tx.spawn_rx();
+    /// assert!(rx.is_empty());
+    ///
+    /// for i in 0..5 {
+    ///     tx.send(i).unwrap();
+    /// }
+    /// // only the last 2 are still there
+    /// assert_eq!(rx.lag(), 5);
+    /// assert_eq!(rx.len(), 2);
+    ///
+    /// assert_eq!(rx.recv(), Err(RecvError::Lagged(3)));
+    /// assert_eq!(rx.recv(), Ok(3));
+    /// assert_eq!((rx.lag(), rx.len()), (1, 1));
+    /// ```
+    pub fn len(&self) -> usize {
+        self.unread(usize::MAX)
+    }
+
+    /// Whether this receiver has read every message sent so far that it wants.
+    pub fn is_empty(&self) -> bool {
+        self.unread(1) == 0
+    }
+
+    /// [`Receiver::len`], but stops counting at `max`.
+    fn unread(&self, max: usize) -> usize {
+        if self.cursor.is_evicted() {
+            return 0;
+        }
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        let from = match self.shared.overflow {
+            Overflow::Lag => self.head.max(self.ring.oldest(tail)),
+            _ => self.head,
+        };
+        self.count_wanted(from, tail, max)
+    }
+
+    /// Stops counting this receiver, so senders no longer wait for it, while keeping its
+    /// place. Its unread messages are credited the way dropping it would.
+    ///
+    /// Resume it with [`PausedReceiver::resume`].
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::builder(2).history(2).build();
+    /// let mut live = tx.spawn_rx();
+    /// let rx = tx.spawn_rx();
+    ///
+    /// let paused = rx.pause();
+    /// // it doesn't hold up the channel in the meantime
+    /// for i in 0..6 {
+    ///     tx.send(i).unwrap();
+    ///     assert_eq!(live.recv(), Ok(i));
+    /// }
+    ///
+    /// // the last 2 messages are kept, so 0 to 3 were missed
+    /// let (mut rx, skipped) = paused.resume(ResumeFrom::Oldest);
+    /// assert_eq!(skipped, 4);
+    /// assert_eq!(rx.recv(), Ok(4));
+    /// assert_eq!(rx.recv(), Ok(5));
+    ///
+    /// let (mut rx, skipped) = rx.pause().resume(ResumeFrom::Tail);
+    /// assert_eq!(skipped, 0);
+    /// tx.send(6).unwrap();
+    /// assert_eq!(rx.recv(), Ok(6));
+    /// ```
+    pub fn pause(self) -> PausedReceiver<T> {
+        // leaving is what dropping does
+        PausedReceiver {
+            shared: Arc::clone(&self.shared),
+            head: self.head,
+            interest: self.cursor.interest,
+        }
+    }
+
+    /// Rejoins the channel after this receiver was evicted, starting at the next
+    /// message sent. Does nothing if it wasn't evicted.
+    pub fn resubscribe(&mut self) {
+        let mut tail = self.shared.lock_tail();
+        if !self.cursor.is_evicted() {
+            return;
+        }
+        self.shared.num_readers.fetch_add(1, Ordering::Release);
+        self.head = tail.pos;
+        self.ring = Arc::clone(&tail.ring);
+        self.cursor.head.store(tail.pos, Ordering::Release);
+        tail.readers.push(Arc::clone(&self.cursor));
+    }
+}
+
+/// Receiving by value clones the message for every receiver but the last. For `T` that
+/// isn't [`Clone`], see [`Receiver::recv_ref`].
+impl<T: Clone> Receiver<T> {
     /// Try to receive a message.
     ///
     /// # Errors
     /// - if there's no new message available
     /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
     pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
         self.recv_inner(RecvCondition::Try).map_err(|e| match e {
             InnerRecvError::Disconnected => TryRecvError::Disconnected,
             InnerRecvError::Empty => TryRecvError::Empty,
+            InnerRecvError::Lagged(missed) => TryRecvError::Lagged(missed),
+            InnerRecvError::Evicted => TryRecvError::Evicted,
+            _ => unreachable!(),
         })
     }
 
-    /// Receive a message. Loops until a message is available.
+    /// Receive a message. Blocks until a message is available.
+    ///
+    /// How the thread waits while the channel is idle depends on the channel's
+    /// [`WaitStrategy`]. By default with the `std` feature, the thread is parked
+    /// and woken by the next send, or when the last [`Channel`] is dropped.
+    /// Without it, this spins.
     ///
     /// # Errors
     /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let handle = std::thread::spawn(move || {
+    ///     let mut received = vec![];
+    ///     while let Ok(msg) = rx.recv() {
+    ///         received.push(msg);
+    ///     }
+    ///     received
+    /// });
+    ///
+    /// std::thread::sleep(std::time::Duration::from_millis(20));
+    /// tx.blocking_send(1).unwrap();
+    /// std::thread::sleep(std::time::Duration::from_millis(20));
+    /// tx.blocking_send(2).unwrap();
+    /// // dropping the last channel wakes the receiver with a disconnect
+    /// drop(tx);
+    ///
+    /// assert_eq!(handle.join().unwrap(), vec![1, 2]);
+    /// ```
     pub fn recv(&mut self) -> Result<T, RecvError> {
         self.recv_inner(RecvCondition::Block).map_err(|e| match e {
             InnerRecvError::Disconnected => RecvError::Disconnected,
+            InnerRecvError::Lagged(missed) => RecvError::Lagged(missed),
+            InnerRecvError::Evicted => RecvError::Evicted,
             _ => unreachable!(),
         })
     }
-    fn recv_inner(&mut self, cond: RecvCondition) -> Result<T, InnerRecvError> {
-        let mut was_closed = false;
+    /// Receive a message, waiting at most `timeout` for one to arrive.
+    ///
+    /// # Errors
+    /// - if no message arrived in time
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// assert_eq!(
+    ///     rx.recv_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Timeout)
+    /// );
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(1));
+    ///
+    /// drop(tx);
+    /// assert_eq!(
+    ///     rx.recv_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Disconnected)
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_deadline(deadline),
+            // too far in the future to represent. that's forever.
+            None => self.recv().map_err(|e| match e {
+                RecvError::Disconnected => RecvTimeoutError::Disconnected,
+                RecvError::Lagged(missed) => RecvTimeoutError::Lagged(missed),
+                RecvError::Evicted => RecvTimeoutError::Evicted,
+            }),
+        }
+    }
+
+    /// Receive a message, waiting until `deadline` for one to arrive.
+    ///
+    /// # Errors
+    /// - if no message arrived before the deadline
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    #[cfg(feature = "std")]
+    pub fn recv_deadline(&mut self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        self.recv_inner(RecvCondition::Deadline(deadline))
+            .map_err(|e| match e {
+                InnerRecvError::Disconnected => RecvTimeoutError::Disconnected,
+                InnerRecvError::Timeout => RecvTimeoutError::Timeout,
+                InnerRecvError::Lagged(missed) => RecvTimeoutError::Lagged(missed),
+                InnerRecvError::Evicted => RecvTimeoutError::Evicted,
+                _ => unreachable!(),
+            })
+    }
+
+    /// Receive a message asynchronously.
+    ///
+    /// This doesn't depend on any runtime. The task is woken by the next send,
+    /// or when the last [`Channel`] is dropped.
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    /// # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    /// #     struct Unparker(std::thread::Thread);
+    /// #     impl std::task::Wake for Unparker {
+    /// #         fn wake(self: std::sync::Arc<Self>) { self.0.unpark() }
+    /// #     }
+    /// #     let waker = std::sync::Arc::new(Unparker(std::thread::current())).into();
+    /// #     let mut cx = std::task::Context::from_waker(&waker);
+    /// #     let mut fut = std::pin::pin!(fut);
+    /// #     loop {
+    /// #         match fut.as_mut().poll(&mut cx) {
+    /// #             std::task::Poll::Ready(out) => return out,
+    /// #             std::task::Poll::Pending => std::thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// std::thread::spawn(move || {
+    ///     tx.blocking_send(1).unwrap();
+    ///     tx.blocking_send(2).unwrap();
+    /// });
+    ///
+    /// block_on(async move {
+    ///     assert_eq!(rx.recv_async().await, Ok(1));
+    ///     assert_eq!(rx.recv_async().await, Ok(2));
+    ///     assert_eq!(rx.recv_async().await, Err(RecvError::Disconnected));
+    /// });
+    /// ```
+    pub fn recv_async(&mut self) -> RecvFuture<'_, T> {
+        RecvFuture { receiver: self }
+    }
+
+    /// Polls for a message, registering the task to be woken when one might be available.
+    ///
+    /// Only the task that polled last is woken.
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        let mut registered = false;
         loop {
-            let tail = self.shared.tail.load(Ordering::Acquire);
-            if tail != self.head {
+            match self.recv_inner(RecvCondition::Try) {
+                Ok(val) => {
+                    self.shared.rx_waiters.unregister(&mut self.waker);
+                    return Poll::Ready(Ok(val));
+                }
+                Err(InnerRecvError::Disconnected) => {
+                    self.shared.rx_waiters.unregister(&mut self.waker);
+                    return Poll::Ready(Err(RecvError::Disconnected));
+                }
+                Err(InnerRecvError::Lagged(missed)) => {
+                    self.shared.rx_waiters.unregister(&mut self.waker);
+                    return Poll::Ready(Err(RecvError::Lagged(missed)));
+                }
+                Err(InnerRecvError::Evicted) => {
+                    self.shared.rx_waiters.unregister(&mut self.waker);
+                    return Poll::Ready(Err(RecvError::Evicted));
+                }
+                Err(_) if registered => return Poll::Pending,
+                Err(_) => {
+                    // check once more after registering, a send might have landed in between
+                    self.shared.rx_waiters.register(&mut self.waker, cx.waker());
+                    registered = true;
+                }
+            }
+        }
+    }
+
+    /// Receive up to `max` messages without waiting, appending them to `buf`.
+    ///
+    /// Cheaper than calling [`Receiver::try_recv`] that many times. The tail is only
+    /// looked at once, the head moves once for the whole batch, and a blocked sender
+    /// is woken once.
+    ///
+    /// Returns how many messages were received. That's at least one, unless `max` is 0.
+    ///
+    /// # Errors
+    /// - if there's no new message available
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// Once at least one message was received, these are reported by the next call instead.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(8);
+    /// let mut rx = tx.spawn_rx();
+    /// for i in 0..5 {
+    ///     tx.send(i).unwrap();
+    /// }
+    ///
+    /// let mut buf = vec![];
+    /// assert_eq!(rx.try_recv_many(&mut buf, 3), Ok(3));
+    /// assert_eq!(rx.try_recv_many(&mut buf, 3), Ok(2));
+    /// assert_eq!(buf, vec![0, 1, 2, 3, 4]);
+    /// assert_eq!(rx.try_recv_many(&mut buf, 3), Err(TryRecvError::Empty));
+    /// ```
+    pub fn try_recv_many(&mut self, buf: &mut Vec<T>, max: usize) -> Result<usize, TryRecvError> {
+        self.recv_many_inner(buf, max, RecvCondition::Try)
+            .map_err(|e| match e {
+                InnerRecvError::Disconnected => TryRecvError::Disconnected,
+                InnerRecvError::Empty => TryRecvError::Empty,
+                InnerRecvError::Lagged(missed) => TryRecvError::Lagged(missed),
+                InnerRecvError::Evicted => TryRecvError::Evicted,
+                _ => unreachable!(),
+            })
+    }
+
+    /// Receive up to `max` messages, appending them to `buf`. Blocks until at least one
+    /// is available. See [`Receiver::try_recv_many`].
+    ///
+    /// Returns how many messages were received. That's at least one, unless `max` is 0.
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(64);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let handle = std::thread::spawn(move || {
+    ///     let mut received = vec![];
+    ///     while rx.recv_many(&mut received, 16).is_ok() {}
+    ///     received
+    /// });
+    ///
+    /// for i in 0..100 {
+    ///     tx.blocking_send(i).unwrap();
+    /// }
+    /// drop(tx);
+    /// assert_eq!(handle.join().unwrap(), (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn recv_many(&mut self, buf: &mut Vec<T>, max: usize) -> Result<usize, RecvError> {
+        self.recv_many_inner(buf, max, RecvCondition::Block)
+            .map_err(|e| match e {
+                InnerRecvError::Disconnected => RecvError::Disconnected,
+                InnerRecvError::Lagged(missed) => RecvError::Lagged(missed),
+                InnerRecvError::Evicted => RecvError::Evicted,
+                _ => unreachable!(),
+            })
+    }
+
+    /// A blocking iterator over messages. Ends once the channel is closed and every
+    /// message has been read, or the receiver is evicted.
+    ///
+    /// With [`Overflow::Lag`], messages the receiver fell too far behind on are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    ///
+    /// let handle = std::thread::spawn(move || rx.iter().map(|n| n * 2).sum::<i32>());
+    /// for i in 1..=10 {
+    ///     tx.blocking_send(i).unwrap();
+    /// }
+    /// drop(tx);
+    /// assert_eq!(handle.join().unwrap(), 110);
+    /// ```
+    pub fn iter(&mut self) -> Iter<'_, T> {
+        Iter { rx: self }
+    }
+
+    /// An iterator over the messages available right now. Ends when there are none left,
+    /// without waiting for more.
+    ///
+    /// With [`Overflow::Lag`], messages the receiver fell too far behind on are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    ///
+    /// assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// tx.send(3).unwrap();
+    /// assert_eq!(rx.try_iter().next(), Some(3));
+    /// ```
+    pub fn try_iter(&mut self) -> TryIter<'_, T> {
+        TryIter { rx: self }
+    }
+
+    /// An iterator over every message sent so far that this receiver hasn't read.
+    /// Messages sent while iterating are left for later.
+    ///
+    /// They're all taken at once, so like [`Vec::drain`], whatever the iterator hasn't
+    /// yielded when it's dropped is gone.
+    ///
+    /// With [`Overflow::Lag`], messages the receiver fell too far behind on are skipped.
+    /// Ends early if the receiver is evicted.
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    ///
+    /// let mut drain = rx.drain();
+    /// assert_eq!(drain.next(), Some(1));
+    /// tx.send(3).unwrap();
+    /// assert_eq!(drain.collect::<Vec<_>>(), vec![2]);
+    /// assert_eq!(rx.try_recv(), Ok(3));
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        let end = self.shared.tail.load(Ordering::Acquire);
+        let mut batch = Vec::with_capacity(end.saturating_sub(self.head).min(self.ring.capacity()));
+
+        let mut freed = false;
+        while self.head < end {
+            if self.head >= self.ring.end() {
+                self.ring = self.ring.next();
+                continue;
+            }
+            let start = self.head;
+            match self.take_until(self.ring.end().min(end), |val| batch.push(val)) {
+                Ok(seat_freed) => freed |= seat_freed,
+                Err(_) => break,
+            }
+            if self.head > start {
+                continue;
+            }
+            // written over. carry on from the oldest message left.
+            match self.recv_inner(RecvCondition::Try) {
+                Ok(msg) => batch.push(msg),
+                Err(InnerRecvError::Lagged(_)) => {}
+                Err(_) => break,
+            }
+        }
+        if freed && self.shared.overflow != Overflow::Lag {
+            self.shared.seat_freed();
+        }
+        Drain {
+            batch: batch.into_iter(),
+            _rx: PhantomData,
+        }
+    }
+
+    fn recv_inner(&mut self, cond: RecvCondition) -> Result<T, InnerRecvError> {
+        let next = Self::next(
+            &self.shared,
+            &mut self.ring,
+            &mut self.head,
+            &self.cursor,
+            cond,
+        )?;
+        let (ret, freed) = next.ring.take(next.pos);
+        drop(next);
+        if freed && self.shared.overflow != Overflow::Lag {
+            self.shared.seat_freed();
+        }
+        Ok(ret)
+    }
+
+    fn recv_many_inner(
+        &mut self,
+        buf: &mut Vec<T>,
+        max: usize,
+        cond: RecvCondition,
+    ) -> Result<usize, InnerRecvError> {
+        if max == 0 {
+            return Ok(0);
+        }
+        // the first message goes the usual way, which waits for it and reports errors
+        let before = buf.len();
+        buf.push(self.recv_inner(cond)?);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        buf.reserve((max - 1).min(tail.saturating_sub(self.head)));
+
+        let mut freed = false;
+        while buf.len() - before < max && self.head < tail {
+            if self.head >= self.ring.end() {
+                self.ring = self.ring.next();
+                continue;
+            }
+            let start = self.head;
+            // messages we don't want are skipped, so this may take fewer
+            let end = self
+                .ring
+                .end()
+                .min(tail)
+                .min(start + max - (buf.len() - before));
+            match self.take_until(end, |val| buf.push(val)) {
+                Ok(seat_freed) => freed |= seat_freed,
+                // the next call reports it
+                Err(_) => break,
+            }
+            if self.head == start {
+                // written over. the next call reports the lag.
                 break;
             }
-            if self.shared.num_writers.load(Ordering::Relaxed) == 0 {
+        }
+        if freed && self.shared.overflow != Overflow::Lag {
+            self.shared.seat_freed();
+        }
+        Ok(buf.len() - before)
+    }
+
+    /// Takes the messages from `head` up to `end`, which have been sent and are all in
+    /// the current ring, and hands the ones this receiver wants to `f`. Returns whether
+    /// that freed up a seat.
+    ///
+    /// With [`Overflow::Lag`], stops early at a message that was written over.
+    fn take_until(&mut self, end: usize, mut f: impl FnMut(T)) -> Result<bool, InnerRecvError> {
+        let start = self.head;
+        let mut freed = false;
+        match self.shared.overflow {
+            Overflow::Lag => {
+                for pos in start..end {
+                    let seat = self.ring.seat(pos);
+                    let _lock = seat.read_lock();
+                    if unsafe { &*seat.state.get() }.pos != pos {
+                        break;
+                    }
+                    self.head = pos + 1;
+                    if !Self::wants(&self.shared, &self.ring, pos, &self.cursor) {
+                        continue;
+                    }
+                    let (val, last) = self.ring.take(pos);
+                    f(val);
+                    freed |= last;
+                }
+                self.cursor.head.store(self.head, Ordering::Release);
+                return Ok(freed);
+            }
+            // claim the whole batch, the way `next` claims a single message
+            Overflow::Evict(_) => {
+                if self
+                    .cursor
+                    .head
+                    .compare_exchange(start, end, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    return Err(InnerRecvError::Evicted);
+                }
+            }
+            Overflow::Block | Overflow::Grow => self.cursor.head.store(end, Ordering::Release),
+        }
+        for pos in start..end {
+            if !Self::wants(&self.shared, &self.ring, pos, &self.cursor) {
+                continue;
+            }
+            let (val, last) = self.ring.take(pos);
+            f(val);
+            freed |= last;
+        }
+        self.head = end;
+        Ok(freed)
+    }
+}
+/// A message [`Receiver::next`] moved past.
+struct Next<'a, T> {
+    ring: &'a Ring<T>,
+    pos: usize,
+    _lock: Option<RwLockReadGuard<'a, ()>>,
+}
+
+/// A message borrowed from the channel by [`Receiver::recv_ref`].
+///
+/// The read is done when this is dropped. Until then, the seat can't be reused, so
+/// don't hold on to it for long. With [`Overflow::Lag`], senders don't wait for it:
+/// they move on to a fresh buffer and leave the borrowed message where it is.
+pub struct RecvRef<'a, T> {
+    shared: &'a State<T>,
+    seat: &'a Seat<T>,
+    _lock: Option<RwLockReadGuard<'a, ()>>,
+}
+
+impl<T> Deref for RecvRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // nobody takes the value while our read is outstanding
+        unsafe { &*self.seat.state.get() }
+            .val
+            .as_ref()
+            .expect("value was taken while borrowed")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RecvRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> Drop for RecvRef<'_, T> {
+    fn drop(&mut self) {
+        if self.seat.finish_read() && self.shared.overflow != Overflow::Lag {
+            self.shared.seat_freed();
+        }
+    }
+}
+
+/// A [`Receiver`] that senders don't wait for, from [`Receiver::pause`].
+pub struct PausedReceiver<T> {
+    shared: Arc<State<T>>,
+    /// where the receiver was paused.
+    head: usize,
+    interest: u64,
+}
+
+/// Where [`PausedReceiver::resume`] picks up again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeFrom {
+    /// At the next message sent.
+    Tail,
+    /// Where the receiver was paused, or if that's gone, at the oldest message the
+    /// channel keeps with [`ChannelBuilder::history`].
+    ///
+    /// Needs history to get anything back. Without it this is the same as
+    /// [`ResumeFrom::Tail`], even with [`Overflow::Lag`]: the last receiver to read a
+    /// message takes it out of the channel, and a paused receiver isn't counted.
+    Oldest,
+}
+
+impl<T> PausedReceiver<T> {
+    /// Counts the receiver again, starting at `from`.
+    ///
+    /// Also returns how many messages it skipped while paused.
+    pub fn resume(self, from: ResumeFrom) -> (Receiver<T>, usize) {
+        let rx = match from {
+            ResumeFrom::Tail => Receiver::join(self.shared, 0, self.interest, 0),
+            ResumeFrom::Oldest => Receiver::join(self.shared, usize::MAX, self.interest, self.head),
+        };
+        let skipped = rx.head - self.head;
+        (rx, skipped)
+    }
+}
+
+/// Iterator returned by [`Receiver::iter`].
+pub struct Iter<'a, T> {
+    rx: &'a mut Receiver<T>,
+}
+
+impl<T: Clone> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            match self.rx.recv() {
+                // missed messages are gone. carry on from the oldest one left.
+                Err(RecvError::Lagged(_)) => continue,
+                msg => return msg.ok(),
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`Receiver::try_iter`].
+pub struct TryIter<'a, T> {
+    rx: &'a mut Receiver<T>,
+}
+
+impl<T: Clone> Iterator for TryIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            match self.rx.try_recv() {
+                Err(TryRecvError::Lagged(_)) => continue,
+                msg => return msg.ok(),
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`Receiver::into_iter`]. See [`Receiver::iter`].
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.iter().next()
+    }
+}
+
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let tx = Channel::new(4);
+/// let rx = tx.spawn_rx();
+/// std::thread::spawn(move || {
+///     for word in ["a", "b", "c"] {
+///         tx.blocking_send(word).unwrap();
+///     }
+/// });
+///
+/// let mut received = String::new();
+/// for word in rx {
+///     received.push_str(word);
+/// }
+/// assert_eq!(received, "abc");
+/// ```
+impl<T: Clone> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a mut Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator returned by [`Receiver::drain`].
+pub struct Drain<'a, T> {
+    batch: vec::IntoIter<T>,
+    _rx: PhantomData<&'a mut Receiver<T>>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.batch.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.batch.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum RecvCondition {
+    Try,
+    Block,
+    #[cfg(feature = "std")]
+    Deadline(Instant),
+}
+
+impl RecvCondition {
+    fn deadline(self) -> Option<Deadline> {
+        match self {
+            #[cfg(feature = "std")]
+            RecvCondition::Deadline(deadline) => Some(deadline),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Try to receive a message without taking it out of the channel.
+    ///
+    /// Unlike [`Receiver::try_recv`], this never clones, so `T` doesn't need to be [`Clone`].
+    ///
+    /// # Errors
+    /// - if there's no new message available
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    pub fn try_recv_ref(&mut self) -> Result<RecvRef<'_, T>, TryRecvError> {
+        self.recv_ref_inner(RecvCondition::Try)
+            .map_err(|e| match e {
+                InnerRecvError::Disconnected => TryRecvError::Disconnected,
+                InnerRecvError::Empty => TryRecvError::Empty,
+                InnerRecvError::Lagged(missed) => TryRecvError::Lagged(missed),
+                InnerRecvError::Evicted => TryRecvError::Evicted,
+                _ => unreachable!(),
+            })
+    }
+
+    /// Receive a message without taking it out of the channel. Blocks until a message
+    /// is available.
+    ///
+    /// Unlike [`Receiver::recv`], this never clones, so `T` doesn't need to be [`Clone`].
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(vec![0u8; 1 << 20]).unwrap();
+    ///
+    /// // look at it in place, without a copy of the megabyte
+    /// let msg = rx.recv_ref().unwrap();
+    /// assert_eq!(msg.len(), 1 << 20);
+    /// ```
+    pub fn recv_ref(&mut self) -> Result<RecvRef<'_, T>, RecvError> {
+        self.recv_ref_inner(RecvCondition::Block)
+            .map_err(|e| match e {
+                InnerRecvError::Disconnected => RecvError::Disconnected,
+                InnerRecvError::Lagged(missed) => RecvError::Lagged(missed),
+                InnerRecvError::Evicted => RecvError::Evicted,
+                _ => unreachable!(),
+            })
+    }
+
+    /// Receive a message and hand it to `f` by reference. Blocks until a message is
+    /// available. See [`Receiver::recv_ref`].
+    ///
+    /// # Errors
+    /// - if the channel is closed
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    ///
+    /// # Example
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(2);
+    /// let mut rx = tx.spawn_rx();
+    /// tx.send(String::from("hello")).unwrap();
+    /// assert_eq!(rx.recv_with(|msg| msg.len()), Ok(5));
+    /// ```
+    pub fn recv_with<R>(&mut self, f: impl FnOnce(&T) -> R) -> Result<R, RecvError> {
+        self.recv_ref().map(|msg| f(&msg))
+    }
+
+    fn recv_ref_inner(&mut self, cond: RecvCondition) -> Result<RecvRef<'_, T>, InnerRecvError> {
+        let next = Self::next(
+            &self.shared,
+            &mut self.ring,
+            &mut self.head,
+            &self.cursor,
+            cond,
+        )?;
+        Ok(RecvRef {
+            shared: &self.shared,
+            seat: next.ring.seat(next.pos),
+            _lock: next._lock,
+        })
+    }
+
+    /// Waits for the message at `head`, and moves past it.
+    ///
+    /// Returns where to read it from. The read has to be counted on the seat afterwards.
+    fn next<'a>(
+        shared: &State<T>,
+        ring: &'a mut Arc<Ring<T>>,
+        head: &mut usize,
+        cursor: &Cursor,
+        cond: RecvCondition,
+    ) -> Result<Next<'a, T>, InnerRecvError> {
+        let mut was_closed = false;
+        let mut blocker = shared.rx_blocker(cond.deadline());
+        let tail = loop {
+            let tail = shared.tail.load(Ordering::Acquire);
+            if tail != *head {
+                if *head >= ring.end() {
+                    // everything in this ring has been read. that might free it.
+                    *ring = ring.next();
+                    continue;
+                }
+                if !Self::wants(shared, ring, *head, cursor) {
+                    // we weren't counted, so there's nothing to read
+                    Self::skip(shared, head, cursor)?;
+                    continue;
+                }
+                break tail;
+            }
+            if cursor.is_evicted() {
+                return Err(InnerRecvError::Evicted);
+            }
+            if shared.num_writers.load(Ordering::Relaxed) == 0 {
                 // from bus docs:
                 //
                 // we need to check again that there's nothing in the bus, otherwise we might have
@@ -78,38 +1117,154 @@ impl<T: Clone> Receiver<T> {
             if cond == RecvCondition::Try {
                 return Err(InnerRecvError::Empty);
             }
+
+            let head = *head;
+            if !blocker.wait(|| {
+                shared.tail.load(Ordering::Acquire) != head
+                    || shared.num_writers.load(Ordering::Relaxed) == 0
+                    || cursor.is_evicted()
+            }) {
+                return Err(InnerRecvError::Timeout);
+            }
+        };
+
+        let ring: &'a Ring<T> = ring;
+        let pos = *head;
+        let mut lock = None;
+        match shared.overflow {
+            // senders don't wait for us. keep them off the seat while it's read.
+            Overflow::Lag => {
+                let seat = ring.seat(pos);
+                let seat_lock = seat.read_lock();
+                if pos < ring.oldest(tail) || unsafe { &*seat.state.get() }.pos != pos {
+                    drop(seat_lock);
+                    // written over. pick up again at the oldest message left.
+                    let oldest = ring.oldest(shared.tail.load(Ordering::Acquire));
+                    *head = oldest;
+                    cursor.head.store(oldest, Ordering::Release);
+                    return Err(InnerRecvError::Lagged((oldest - pos) as u64));
+                }
+                lock = Some(seat_lock);
+                cursor.head.store(pos + 1, Ordering::Release);
+            }
+            // claim the message first, so a sender evicting us can tell whether we've read it.
+            Overflow::Evict(_) => {
+                if cursor
+                    .head
+                    .compare_exchange(pos, pos + 1, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    return Err(InnerRecvError::Evicted);
+                }
+            }
+            Overflow::Block | Overflow::Grow => cursor.head.store(pos + 1, Ordering::Release),
         }
+        *head = pos + 1;
+        Ok(Next {
+            ring,
+            pos,
+            _lock: lock,
+        })
+    }
 
-        let head = self.head;
-        let ret = self.shared.ring[head].take();
-        self.head = (head + 1) % self.shared.len;
-        Ok(ret)
+    /// Whether the receiver was counted on the message at `pos`, which has been sent.
+    ///
+    /// With [`Overflow::Lag`], a message that was written over counts as wanted, and
+    /// is reported as lagged when read.
+    fn wants(shared: &State<T>, ring: &Ring<T>, pos: usize, cursor: &Cursor) -> bool {
+        if cursor.interest == Cursor::ALL {
+            return true;
+        }
+        match ring.seat(pos).tags(pos) {
+            Some(tags) => cursor.wants(tags),
+            // a seat we're counted on isn't written over until we've read it
+            None => shared.overflow == Overflow::Lag,
+        }
+    }
+
+    /// Moves past the message at `head` without reading it.
+    fn skip(shared: &State<T>, head: &mut usize, cursor: &Cursor) -> Result<(), InnerRecvError> {
+        let pos = *head;
+        if let Overflow::Evict(_) = shared.overflow {
+            // claimed like any other message, so an eviction can't credit it twice
+            if cursor
+                .head
+                .compare_exchange(pos, pos + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+            {
+                return Err(InnerRecvError::Evicted);
+            }
+        } else {
+            cursor.head.store(pos + 1, Ordering::Release);
+        }
+        *head = pos + 1;
+        Ok(())
+    }
+
+    /// Whether a receive would return right away, with a message or a disconnect.
+    pub(crate) fn is_ready(&self) -> bool {
+        if self.shared.num_writers.load(Ordering::Relaxed) == 0 || self.cursor.is_evicted() {
+            return true;
+        }
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        // messages we don't want would be skipped, so look for one we do
+        self.count_wanted(self.head, tail, 1) > 0
+    }
+
+    /// Counts the messages from `from` up to `tail` that this receiver wants, stopping
+    /// once there are `max`.
+    fn count_wanted(&self, from: usize, tail: usize, max: usize) -> usize {
+        if self.cursor.interest == Cursor::ALL {
+            return tail.saturating_sub(from).min(max);
+        }
+        let mut ring = Arc::clone(&self.ring);
+        let mut pos = from;
+        let mut wanted = 0;
+        while pos < tail && wanted < max {
+            if pos >= ring.end() {
+                ring = ring.next();
+                continue;
+            }
+            if Self::wants(&self.shared, &ring, pos, &self.cursor) {
+                wanted += 1;
+            }
+            pos += 1;
+        }
+        wanted
     }
-}
-#[derive(PartialEq, Eq)]
-pub(crate) enum RecvCondition {
-    Try,
-    Block,
 }
 
-impl<T: Clone> Clone for Receiver<T> {
+/// Starts at the next message sent, not where this receiver is. See [`Receiver::fork`].
+impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
-        Receiver::new(Arc::clone(&self.shared))
+        self.subscribe()
     }
 }
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
+        self.shared.rx_waiters.unregister(&mut self.waker);
+
+        // leave under the tail lock, so we credit exactly the messages that counted us.
+        let mut tail_lock = self.shared.lock_tail();
+        if self.cursor.is_evicted() {
+            // the sender that evicted us did all of this already
+            return;
+        }
+        tail_lock
+            .readers
+            .retain(|cursor| !Arc::ptr_eq(cursor, &self.cursor));
         self.shared.num_readers.fetch_sub(1, Ordering::Release);
-        let mut cur = self.head;
-        let tail = self.shared.tail.load(Ordering::SeqCst);
         // this probably means that some readers will lose info.
-        while cur != tail {
-            #[cfg(feature = "debug")]
-            tracing::info!("Drop Proc: \nAdding 1 to {cur}");
-            self.shared.ring[cur]
-                .num_reads
-                .fetch_add(1, Ordering::Release);
-            cur = (cur + 1) % self.shared.len;
+        let freed = tail_lock
+            .ring
+            .skip_reads(self.head, tail_lock.pos, &self.cursor);
+        drop(tail_lock);
+
+        if self.shared.num_readers.load(Ordering::Acquire) == 0 {
+            // nobody is left to read. senders waiting for room need to find out.
+            self.shared.notify_senders();
+        } else if freed {
+            self.shared.seat_freed();
         }
     }
 }
@@ -0,0 +1,159 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::{
+    mutex::{Mutex, RwLockWriteGuard, lock},
+    prelude::*,
+};
+
+/// A ring buffer of seats, holding the messages sent from `start` on.
+///
+/// Senders write to the newest ring. When a channel grows, a new ring takes over at
+/// the tail and this one is sealed. It keeps the messages it already holds, and is
+/// freed once every receiver has moved on to the next ring.
+pub(crate) struct Ring<T> {
+    seats: Vec<Seat<T>>,
+    /// how many messages stay in the ring after everyone has read them.
+    pub(crate) history: usize,
+    /// position of the first message in this ring.
+    pub(crate) start: usize,
+    /// position of the first message in the next ring, once there is one.
+    end: AtomicUsize,
+    next: Mutex<Option<Arc<Ring<T>>>>,
+}
+
+impl<T> Ring<T> {
+    /// A ring holding up to `capacity` unread messages and the last `history` read ones,
+    /// the first of which will be at `start`.
+    pub(crate) fn new(capacity: usize, history: usize, start: usize) -> Self {
+        // needs one padding element
+        let len = capacity + history + 1;
+        Self {
+            seats: (0..len).map(|_| Seat::default()).collect(),
+            history,
+            start,
+            end: AtomicUsize::new(usize::MAX),
+            next: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// The number of unread messages the ring holds.
+    pub(crate) fn capacity(&self) -> usize {
+        // one seat is padding
+        self.len() - 1 - self.history
+    }
+
+    pub(crate) fn seat(&self, pos: usize) -> &Seat<T> {
+        &self.seats[pos % self.len()]
+    }
+
+    /// Locks the seats for the `n` messages from `pos` for writing. Unless told to `wait`,
+    /// gives up if a receiver is reading one of them. Only needed with [`Overflow::Lag`].
+    pub(crate) fn lock_seats(
+        &self,
+        pos: usize,
+        n: usize,
+        wait: bool,
+    ) -> Option<Vec<RwLockWriteGuard<'_, ()>>> {
+        (pos..pos + n)
+            .map(|pos| match wait {
+                true => Some(self.seat(pos).write_lock()),
+                false => self.seat(pos).try_write_lock(),
+            })
+            .collect()
+    }
+
+    /// Reads the message at `pos`. See [`Seat::take`].
+    pub(crate) fn take(&self, pos: usize) -> (T, bool)
+    where
+        T: Clone,
+    {
+        // history has to stay readable
+        self.seat(pos).take(self.history == 0)
+    }
+
+    #[cfg(feature = "debug")]
+    pub(crate) fn seats(&self) -> &[Seat<T>] {
+        &self.seats
+    }
+
+    /// The oldest message still in the ring, when `tail` is the next one to be sent.
+    /// Only older than `tail - capacity` with [`Overflow::Lag`] or history.
+    pub(crate) fn oldest(&self, tail: usize) -> usize {
+        tail.min(self.end())
+            .saturating_sub(self.len() - 1)
+            .max(self.start)
+    }
+
+    /// Where the next ring takes over. `usize::MAX` while senders still write here.
+    pub(crate) fn end(&self) -> usize {
+        self.end.load(Ordering::Acquire)
+    }
+
+    /// Hands over to `next`, which starts at the tail. The caller holds the tail lock.
+    pub(crate) fn seal(&self, next: Arc<Ring<T>>) {
+        let end = next.start;
+        *lock(&self.next) = Some(next);
+        // receivers only look for the next ring once they've seen the end
+        self.end.store(end, Ordering::Release);
+    }
+
+    /// The ring after this one. Only call once the end has been seen.
+    pub(crate) fn next(&self) -> Arc<Ring<T>> {
+        Arc::clone(lock(&self.next).as_ref().expect("ring was sealed"))
+    }
+
+    /// Whether the `n` seats from `tail` on can be written to. `n` is at most the capacity.
+    ///
+    /// The seat after each (the fence) must have been read by everyone, otherwise
+    /// receivers couldn't tell a full ring from an empty one. With history, the fence
+    /// is further along, so the seats in between keep their messages.
+    pub(crate) fn has_room(&self, tail: usize, n: usize) -> bool {
+        (tail..tail + n).all(|pos| {
+            let fence = self.seat(pos + 1 + self.history);
+            let required_reads = fence.required_reads.load(Ordering::SeqCst);
+            required_reads.saturating_sub(fence.num_reads.load(Ordering::SeqCst)) == 0
+        })
+    }
+
+    /// Counts a reader at `head` as having read everything it wants up to `tail`,
+    /// for when it leaves without reading. The caller holds the tail lock.
+    ///
+    /// Only seats that senders may still write to matter, so older rings are left alone.
+    /// Returns whether that freed up a seat.
+    pub(crate) fn skip_reads(&self, head: usize, tail: usize, cursor: &Cursor) -> bool {
+        let mut freed = false;
+        // with `Overflow::Lag` older seats have been written over already
+        for pos in head.max(self.start).max(tail.saturating_sub(self.len()))..tail {
+            #[cfg(feature = "debug")]
+            tracing::info!("Drop Proc: \nAdding 1 to {pos}");
+            let seat = self.seat(pos);
+            let state = unsafe { &*seat.state.get() };
+            if state.pos != pos || !cursor.wants(seat.tags(pos).unwrap_or(0)) {
+                continue;
+            }
+            let num_reads = seat.num_reads.fetch_add(1, Ordering::Release) + 1;
+            freed |= num_reads >= seat.required_reads.load(Ordering::SeqCst);
+        }
+        freed
+    }
+
+    /// Counts one more reader for the messages from `head` to `tail` that are still
+    /// in this ring, and that it wants. The caller holds the tail lock.
+    pub(crate) fn add_reader(&self, head: usize, tail: usize, cursor: &Cursor) {
+        let end = tail.min(self.end());
+        for pos in head.max(self.start).max(end.saturating_sub(self.len()))..end {
+            let seat = self.seat(pos);
+            // with `Overflow::Lag` it may have been written over already
+            if unsafe { &*seat.state.get() }.pos == pos && cursor.wants(seat.tags(pos).unwrap_or(0))
+            {
+                seat.required_reads.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
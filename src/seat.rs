@@ -2,9 +2,14 @@ use core::{
     cell::UnsafeCell,
     fmt,
     ops::Deref,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
 };
 
+#[cfg(feature = "std")]
+use std::sync::TryLockError;
+
+use crate::mutex::{RwLock, RwLockReadGuard, RwLockWriteGuard, read};
+
 /// A slot in the ring buffer that holds a value and tracks read operations.
 pub(crate) struct Seat<T> {
     // the number of reads.
@@ -15,6 +20,16 @@ pub(crate) struct Seat<T> {
     // In the event a read and a write happen at the same time,
     // the channel will fail first
     pub(crate) num_reads: AtomicUsize,
+    // the number of receivers the value was sent to. Only changes while it's being
+    // read when a receiver that hasn't read it yet is forked.
+    pub(crate) required_reads: AtomicUsize,
+    // the tags of the message at position `tagged`, for receivers that only want some
+    // messages. Readable without a lock, see `Seat::tags`.
+    tags: AtomicU64,
+    tagged: AtomicUsize,
+    // only taken with `Overflow::Lag`, where a sender may overwrite
+    // the seat while someone is still reading it.
+    lock: RwLock<()>,
     pub(crate) state: MutSeatState<T>,
 }
 
@@ -22,42 +37,112 @@ impl<T> Default for Seat<T> {
     fn default() -> Self {
         Self {
             num_reads: AtomicUsize::new(0),
-            state: MutSeatState(UnsafeCell::new(SeatState {
-                required_reads: 0,
-                val: None,
-            })),
+            required_reads: AtomicUsize::new(0),
+            tags: AtomicU64::new(0),
+            tagged: AtomicUsize::new(0),
+            lock: RwLock::new(()),
+            state: MutSeatState(UnsafeCell::new(SeatState { pos: 0, val: None })),
         }
     }
 }
 
+impl<T> Seat<T> {
+    /// Keeps senders from overwriting the seat while it's held.
+    pub(crate) fn read_lock(&self) -> RwLockReadGuard<'_, ()> {
+        read(&self.lock)
+    }
+
+    /// The tags of the message at `pos`, or `None` if the seat holds a later one by now.
+    ///
+    /// Unlike the value, these can be read while a sender overwrites the seat.
+    pub(crate) fn tags(&self, pos: usize) -> Option<u64> {
+        if self.tagged.load(Ordering::SeqCst) != pos {
+            return None;
+        }
+        let tags = self.tags.load(Ordering::SeqCst);
+        // still the same message, so the tags are its own
+        (self.tagged.load(Ordering::SeqCst) == pos).then_some(tags)
+    }
+
+    /// Tags the message being written at `pos`.
+    pub(crate) fn set_tags(&self, pos: usize, tags: u64) {
+        // readers checking the old message see it's gone before the tags change
+        self.tagged.store(usize::MAX, Ordering::SeqCst);
+        self.tags.store(tags, Ordering::SeqCst);
+        self.tagged.store(pos, Ordering::SeqCst);
+    }
+
+    /// Waits for readers holding [`Seat::read_lock`] to finish.
+    pub(crate) fn write_lock(&self) -> RwLockWriteGuard<'_, ()> {
+        #[cfg(feature = "std")]
+        return self.lock.write().unwrap_or_else(|e| e.into_inner());
+
+        #[cfg(not(feature = "std"))]
+        return self.lock.write();
+    }
+
+    /// Locks the seat for writing, unless someone holds [`Seat::read_lock`].
+    pub(crate) fn try_write_lock(&self) -> Option<RwLockWriteGuard<'_, ()>> {
+        #[cfg(feature = "std")]
+        return match self.lock.try_write() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+
+        #[cfg(not(feature = "std"))]
+        return self.lock.try_write();
+    }
+}
+
 impl<T> fmt::Debug for Seat<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Seat")
             .field("num_reads", &self.num_reads)
+            .field("required_reads", &self.required_reads)
+            .field("tags", &self.tags)
             .field("state", &self.state)
             .finish()
     }
 }
 
 impl<T: Clone> Seat<T> {
-    pub(crate) fn take(&self) -> T {
+    /// Reads the value, cloning it for every reader but the last. The last one moves it
+    /// out, unless it has to stay for later readers.
+    ///
+    /// Also returns whether this was the last read, which frees the seat for writing.
+    pub(crate) fn take(&self, move_last: bool) -> (T, bool) {
         let num_reads = self.num_reads.load(Ordering::SeqCst);
         let state = unsafe { &*self.state.get() };
-        let required_reads = state.required_reads;
+        let required_reads = self.required_reads.load(Ordering::SeqCst);
         assert!(
             num_reads < required_reads,
             "num_reads: {num_reads}, req: {}",
             required_reads
         );
 
-        let value = if num_reads + 1 == state.required_reads {
-            unsafe { &mut *self.state.get() }.val.take().unwrap()
-        } else {
-            state.val.clone().unwrap()
-        };
+        // every other reader is done with it, so nobody else touches the value
+        if move_last && num_reads + 1 == required_reads {
+            let value = unsafe { &mut *self.state.get() }.val.take().unwrap();
+            self.num_reads.fetch_add(1, Ordering::SeqCst);
+            return (value, true);
+        }
+
+        let value = state.val.clone().unwrap();
+        // readers finishing together see different counts here, so one of them is last
+        let num_reads = self.num_reads.fetch_add(1, Ordering::SeqCst) + 1;
+        let last = num_reads >= self.required_reads.load(Ordering::SeqCst);
+        (value, last)
+    }
+}
 
-        self.num_reads.fetch_add(1, Ordering::SeqCst);
-        value
+impl<T> Seat<T> {
+    /// Counts a read that borrowed the value instead of taking it.
+    ///
+    /// Returns whether this was the last read, which frees the seat for writing.
+    pub(crate) fn finish_read(&self) -> bool {
+        let num_reads = self.num_reads.fetch_add(1, Ordering::SeqCst) + 1;
+        num_reads >= self.required_reads.load(Ordering::SeqCst)
     }
 }
 
@@ -65,12 +150,13 @@ pub(crate) struct MutSeatState<T>(UnsafeCell<SeatState<T>>);
 impl<T> fmt::Debug for MutSeatState<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("MutSeatState")
-            .field(&unsafe { &*self.0.get() }.required_reads)
+            .field(&unsafe { &*self.0.get() }.pos)
             .finish()
     }
 }
-unsafe impl<T> Send for MutSeatState<T> {}
-unsafe impl<T> Sync for MutSeatState<T> {}
+// values are moved out on other threads, and shared between them by `recv_ref`.
+unsafe impl<T: Send> Send for MutSeatState<T> {}
+unsafe impl<T: Send + Sync> Sync for MutSeatState<T> {}
 impl<T> Deref for MutSeatState<T> {
     type Target = UnsafeCell<SeatState<T>>;
     fn deref(&self) -> &Self::Target {
@@ -80,6 +166,7 @@ impl<T> Deref for MutSeatState<T> {
 
 /// State of a seat in the ring buffer containing the value and read requirements.
 pub struct SeatState<T> {
-    pub(crate) required_reads: usize,
+    /// position of the message in the channel, counting every message ever sent.
+    pub(crate) pos: usize,
     pub(crate) val: Option<T>,
 }
@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use alloc::{sync::Arc, task::Wake, vec};
+#[cfg(feature = "std")]
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    task::Waker,
+};
+#[cfg(feature = "std")]
+use std::{
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::prelude::*;
+
+/// A receiver that [`Select`] can wait on, whatever its message type.
+trait Selectable {
+    fn is_ready(&self) -> bool;
+    #[cfg(feature = "std")]
+    fn waiters(&self) -> &WaitList;
+}
+
+impl<T> Selectable for Receiver<T> {
+    fn is_ready(&self) -> bool {
+        Receiver::is_ready(self)
+    }
+    #[cfg(feature = "std")]
+    fn waiters(&self) -> &WaitList {
+        &self.shared.rx_waiters
+    }
+}
+
+/// Waits on several [`Receiver`]s at once, which may carry different message types.
+///
+/// A receiver is ready when receiving from it won't block: there's a message, or its
+/// channel has closed. Selecting returns the index of a ready receiver, in the order
+/// they were added, and you then receive from it with [`Receiver::try_recv`].
+///
+/// By default, when several receivers are ready, they take turns. Use
+/// [`Select::new_biased`] to always prefer the one added first. A select that's built
+/// again for every receive picks up its turn with [`Select::start_at`].
+///
+/// # Example
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let numbers = Channel::new(4);
+/// let words = Channel::new(4);
+/// let mut rx_numbers = numbers.spawn_rx();
+/// let mut rx_words = words.spawn_rx();
+///
+/// std::thread::spawn(move || {
+///     numbers.blocking_send(1).unwrap();
+///     words.blocking_send("one").unwrap();
+/// });
+///
+/// let mut received = (vec![], vec![]);
+/// let mut next = 0;
+/// while received.0.len() + received.1.len() < 2 {
+///     let index = {
+///         let mut sel = Select::new();
+///         sel.recv(&rx_numbers);
+///         sel.recv(&rx_words);
+///         sel.start_at(next);
+///         sel.select()
+///     };
+///     next = index + 1;
+///     match index {
+///         0 => received.0.push(rx_numbers.try_recv().unwrap()),
+///         _ => received.1.push(rx_words.try_recv().unwrap()),
+///     }
+/// }
+/// assert_eq!(received, (vec![1], vec!["one"]));
+/// ```
+pub struct Select<'a> {
+    handles: Vec<&'a dyn Selectable>,
+    biased: bool,
+    /// where the next scan starts looking.
+    start: usize,
+}
+
+impl Default for Select<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Select<'a> {
+    /// Create a select where ready receivers take turns.
+    pub fn new() -> Self {
+        Self {
+            handles: Vec::new(),
+            biased: false,
+            start: 0,
+        }
+    }
+
+    /// Create a select that always prefers the receiver added first.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let urgent = Channel::new(4);
+    /// let normal = Channel::new(4);
+    /// let rx_urgent = urgent.spawn_rx();
+    /// let rx_normal = normal.spawn_rx();
+    /// urgent.send(1).unwrap();
+    /// normal.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new_biased();
+    /// sel.recv(&rx_urgent);
+    /// sel.recv(&rx_normal);
+    /// for _ in 0..4 {
+    ///     assert_eq!(sel.try_select(), Ok(0));
+    /// }
+    /// ```
+    pub fn new_biased() -> Self {
+        Self {
+            handles: Vec::new(),
+            biased: true,
+            start: 0,
+        }
+    }
+
+    /// Adds a receiver, returning the index that identifies it.
+    pub fn recv<T>(&mut self, rx: &'a Receiver<T>) -> usize {
+        self.handles.push(rx);
+        self.handles.len() - 1
+    }
+
+    /// Looks at the receiver at `index` first, and then the ones after it.
+    ///
+    /// Pass one past the index picked last time to take turns with a select that's
+    /// built again for every receive. A biased select only starts there once.
+    pub fn start_at(&mut self, index: usize) {
+        self.start = index;
+    }
+
+    fn scan(&mut self) -> Option<usize> {
+        let len = self.handles.len();
+        if len == 0 {
+            return None;
+        }
+        let start = self.start % len;
+        let index = (start..len)
+            .chain(0..start)
+            .find(|&i| self.handles[i].is_ready())?;
+        // the next one gets first go next time
+        self.start = if self.biased { 0 } else { index + 1 };
+        Some(index)
+    }
+
+    /// Returns the index of a ready receiver, if there is one.
+    ///
+    /// # Errors
+    /// - if no receiver is ready
+    pub fn try_select(&mut self) -> Result<usize, TrySelectError> {
+        self.scan().ok_or(TrySelectError::Empty)
+    }
+
+    /// Blocks until a receiver is ready, and returns its index.
+    ///
+    /// With the `std` feature, the thread is parked until one of the channels
+    /// wakes it up. Without it, this spins.
+    ///
+    /// # Panics
+    /// - if no receivers were added
+    pub fn select(&mut self) -> usize {
+        assert!(!self.handles.is_empty(), "no receivers to select from");
+
+        #[cfg(feature = "std")]
+        return self.select_inner(None).unwrap();
+
+        #[cfg(not(feature = "std"))]
+        loop {
+            if let Some(index) = self.scan() {
+                return index;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Waits at most `timeout` for a receiver to be ready, and returns its index.
+    ///
+    /// # Errors
+    /// - if no receiver was ready in time
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use trotcast::prelude::*;
+    ///
+    /// let tx = Channel::new(4);
+    /// let rx = tx.spawn_rx();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx);
+    /// assert_eq!(
+    ///     sel.select_timeout(Duration::from_millis(10)),
+    ///     Err(SelectTimeoutError::Timeout)
+    /// );
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(sel.select_timeout(Duration::from_millis(10)), Ok(0));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn select_timeout(&mut self, timeout: Duration) -> Result<usize, SelectTimeoutError> {
+        self.select_inner(Instant::now().checked_add(timeout))
+    }
+
+    /// Waits until `deadline` for a receiver to be ready, and returns its index.
+    ///
+    /// # Errors
+    /// - if no receiver was ready before the deadline
+    #[cfg(feature = "std")]
+    pub fn select_deadline(&mut self, deadline: Instant) -> Result<usize, SelectTimeoutError> {
+        self.select_inner(Some(deadline))
+    }
+
+    #[cfg(feature = "std")]
+    fn select_inner(&mut self, deadline: Option<Instant>) -> Result<usize, SelectTimeoutError> {
+        loop {
+            if let Some(index) = self.scan() {
+                return Ok(index);
+            }
+
+            // one waker is registered with every channel, whichever wakes first unparks us.
+            let unparker = Arc::new(Unparker {
+                thread: thread::current(),
+                notified: AtomicBool::new(false),
+            });
+            let waker = Waker::from(Arc::clone(&unparker));
+            let mut ids = vec![None; self.handles.len()];
+            for (handle, id) in self.handles.iter().zip(ids.iter_mut()) {
+                handle.waiters().register(id, &waker);
+            }
+
+            // check once more after registering, a send might have landed in between
+            let mut ready = self.scan();
+            let mut timed_out = false;
+            while ready.is_none() && !unparker.notified.load(Ordering::Acquire) {
+                match deadline {
+                    None => thread::park(),
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            timed_out = true;
+                            break;
+                        }
+                        thread::park_timeout(deadline - now);
+                    }
+                }
+            }
+
+            for (handle, id) in self.handles.iter().zip(ids.iter_mut()) {
+                handle.waiters().unregister(id);
+            }
+            if ready.is_none() {
+                ready = self.scan();
+            }
+            match ready {
+                Some(index) => return Ok(index),
+                None if timed_out => return Err(SelectTimeoutError::Timeout),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Unparks a thread blocked in [`Select::select`].
+#[cfg(feature = "std")]
+struct Unparker {
+    thread: Thread,
+    notified: AtomicBool,
+}
+
+#[cfg(feature = "std")]
+impl Wake for Unparker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.notified.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
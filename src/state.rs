@@ -1,40 +1,316 @@
-use core::sync::atomic::AtomicUsize;
+use core::{
+    ops::DerefMut,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use alloc::vec::Vec;
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::time::Instant;
 
-use crate::prelude::*;
+use crate::{mutex::lock, prelude::*};
 
-/// Wrapper for the tail position in the ring buffer.
+/// The tail position in the ring buffer, and everything else senders keep track of.
+///
+/// Positions count every message ever sent. The seat for a position is `pos % len`.
+pub(crate) struct Tail<T> {
+    pub(crate) pos: usize,
+    /// the ring senders write to.
+    pub(crate) ring: Arc<Ring<T>>,
+    /// every receiver currently counted in `num_readers`.
+    pub(crate) readers: Vec<Arc<Cursor>>,
+    /// how long the ring has been full, for [`Overflow::Evict`].
+    stall: Stall,
+}
+
+impl<T> Tail<T> {
+    /// Moves the tail after a send, which found the ring had room.
+    pub(crate) fn advance(&mut self, pos: usize) {
+        self.pos = pos;
+        self.stall = Stall::default();
+    }
+
+    /// Hands the tail over to a new ring holding `capacity` messages. The old ring
+    /// keeps what it holds for the receivers that are still reading it.
+    pub(crate) fn replace_ring(&mut self, capacity: usize) {
+        let next = Arc::new(Ring::new(capacity, self.ring.history, self.pos));
+        self.ring.seal(Arc::clone(&next));
+        self.ring = next;
+    }
+
+    /// The most messages any receiver has yet to read.
+    pub(crate) fn unread(&self) -> usize {
+        self.readers
+            .iter()
+            .map(|cursor| self.pos.saturating_sub(cursor.head.load(Ordering::Acquire)))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// What senders know about a receiver.
+pub(crate) struct Cursor {
+    /// the tags of the messages the receiver wants. See [`Channel::spawn_rx_interested`].
+    pub(crate) interest: u64,
+    /// the next position the receiver reads. With [`Overflow::Evict`], receivers claim
+    /// each position before reading it, and this is [`Cursor::EVICTED`] once a sender
+    /// detached the receiver.
+    pub(crate) head: AtomicUsize,
+}
+
+impl Cursor {
+    pub(crate) const EVICTED: usize = usize::MAX;
+    /// the interest of a receiver that wants every message, whatever its tags.
+    pub(crate) const ALL: u64 = u64::MAX;
+
+    pub(crate) fn new(head: usize, interest: u64) -> Self {
+        Self {
+            interest,
+            head: AtomicUsize::new(head),
+        }
+    }
+
+    /// Whether the receiver wants a message with `tags`, and is counted in its
+    /// `required_reads`.
+    pub(crate) fn wants(&self, tags: u64) -> bool {
+        self.interest == Self::ALL || self.interest & tags != 0
+    }
+
+    pub(crate) fn is_evicted(&self) -> bool {
+        self.head.load(Ordering::Acquire) == Self::EVICTED
+    }
+}
+
+/// Sends that found the ring full since the last one that didn't.
 #[derive(Default)]
-pub(crate) struct Tail(pub usize);
+struct Stall {
+    failed_sends: usize,
+    #[cfg(feature = "std")]
+    since: Option<Instant>,
+    /// whether a timer will wake the senders when it's due. See [`State::arm_eviction_alarm`].
+    #[cfg(feature = "std")]
+    alarm: bool,
+}
+
+/// Picks the tags of a message. See [`ChannelBuilder::tags`].
+pub(crate) type Tagger<T> = Box<dyn Fn(&T) -> u64 + Send + Sync>;
 
 /// Core state of the broadcast channel managing the ring buffer and synchronization.
 pub struct State<T> {
-    /// a ring buffer.
-    pub(crate) ring: Vec<Seat<T>>,
     /// in theory, this is used to point where the tail will be.
     pub(crate) tail: AtomicUsize,
     /// This ensure that the state of `tail` is written to one at a time.
-    pub(crate) internal_tail: crate::mutex::Mutex<Tail>,
+    pub(crate) internal_tail: crate::mutex::Mutex<Tail<T>>,
     /// This keeps track of number of values to add to the writer_tail
     /// once the writer to writer_tail + 1 is complete
     pub(crate) num_writers: AtomicUsize,
-    pub(crate) len: usize,
+    /// what senders do when the ring is full.
+    pub(crate) overflow: Overflow,
+    /// tags each message for [`Channel::spawn_rx_interested`].
+    pub(crate) tagger: Option<Tagger<T>>,
+    /// whether senders wait for a seat that's being read. See
+    /// [`ChannelBuilder::wait_for_reads`].
+    pub(crate) wait_for_reads: bool,
     /// keeps track of readers
     pub(crate) num_readers: AtomicUsize,
+    /// consumer groups with members, by name. See [`Channel::join_group`].
+    pub(crate) groups: crate::mutex::Mutex<Vec<Weak<GroupState<T>>>>,
+    /// receivers waiting in [`Receiver::recv`] or [`Receiver::recv_async`] until the
+    /// tail moves or the channel closes.
+    pub(crate) rx_waiters: WaitList,
+    /// senders parked in [`Channel::blocking_send`] until the fence seat has been read.
+    pub(crate) tx_waiters: Arc<WaitList>,
+    /// how blocked senders and receivers wait.
+    pub(crate) strategy: Box<dyn WaitStrategy>,
 }
 
-impl<T: Clone> State<T> {
-    pub(crate) fn new(mut len: usize) -> Self {
-        // needs one padding element
-        len += 1;
+impl<T> State<T> {
+    pub(crate) fn new(
+        capacity: usize,
+        history: usize,
+        overflow: Overflow,
+        tagger: Option<Tagger<T>>,
+        strategy: Box<dyn WaitStrategy>,
+        wait_for_reads: bool,
+    ) -> Self {
         Self {
-            ring: (0..len).map(|_| Seat::default()).collect(),
             tail: AtomicUsize::new(0),
-            internal_tail: crate::mutex::Mutex::new(Tail::default()),
+            internal_tail: crate::mutex::Mutex::new(Tail {
+                pos: 0,
+                ring: Arc::new(Ring::new(capacity, history, 0)),
+                readers: Vec::new(),
+                stall: Stall::default(),
+            }),
             num_writers: AtomicUsize::new(0),
-            len,
+            overflow,
+            tagger,
+            wait_for_reads,
             num_readers: AtomicUsize::new(0),
+            groups: crate::mutex::Mutex::new(Vec::new()),
+            rx_waiters: WaitList::new(),
+            tx_waiters: Arc::new(WaitList::new()),
+            strategy,
         }
     }
 }
+
+impl<T> State<T> {
+    /// Takes sole access to the tail.
+    pub(crate) fn lock_tail(&self) -> impl DerefMut<Target = Tail<T>> + '_ {
+        lock(&self.internal_tail)
+    }
+
+    /// Whether a sender can write `n` messages at the tail without waiting.
+    pub(crate) fn has_room(&self, tail: &Tail<T>, n: usize) -> bool {
+        n <= tail.ring.capacity()
+            && (self.overflow == Overflow::Lag || tail.ring.has_room(tail.pos, n))
+    }
+
+    /// With [`Overflow::Grow`], hands the tail over to a new ring, leaving the full one
+    /// to the receivers that are still reading it.
+    ///
+    /// Returns whether there's room for `n` messages now. The caller holds the tail lock.
+    pub(crate) fn grow(&self, tail: &mut Tail<T>, n: usize) -> bool {
+        if self.overflow != Overflow::Grow {
+            return false;
+        }
+        #[cfg(feature = "debug")]
+        tracing::info!("Growing at {}", tail.pos);
+        let capacity = tail.ring.capacity().max(n);
+        tail.replace_ring(capacity);
+        true
+    }
+
+    /// With [`Overflow::Evict`], counts a send that found the ring full. Once the
+    /// [`Eviction`] policy is due, detaches every receiver that has yet to read one of the
+    /// messages in the fence seats for `n` messages, crediting its pending reads the way
+    /// dropping it would.
+    ///
+    /// Returns whether there's room now. The caller holds the tail lock.
+    pub(crate) fn evict(&self, tail: &mut Tail<T>, n: usize, new_send: bool) -> bool {
+        let Overflow::Evict(eviction) = self.overflow else {
+            return false;
+        };
+        // a send counts once, however often it checks again while it waits
+        if new_send {
+            tail.stall.failed_sends += 1;
+        }
+        #[cfg(feature = "std")]
+        let since = *tail.stall.since.get_or_insert_with(Instant::now);
+        let due = match eviction {
+            Eviction::FailedSends(sends) => tail.stall.failed_sends >= sends,
+            #[cfg(feature = "std")]
+            Eviction::After(timeout) => since.elapsed() >= timeout,
+        };
+        if !due {
+            return false;
+        }
+
+        // the ring is full, so the last fence seat has been written to.
+        let fence = tail.pos + n - 1 - tail.ring.capacity();
+        let pos = tail.pos;
+        let ring = Arc::clone(&tail.ring);
+        // a receiver that doesn't want any of them isn't holding anything up
+        let holds_up = |cursor: &Cursor, head: usize| {
+            (head.max((fence + 1).saturating_sub(n))..=fence).any(|pos| {
+                ring.seat(pos)
+                    .tags(pos)
+                    .is_some_and(|tags| cursor.wants(tags))
+            })
+        };
+        tail.readers.retain(|cursor| {
+            loop {
+                let head = cursor.head.load(Ordering::Acquire);
+                if head > fence || !holds_up(cursor, head) {
+                    return true;
+                }
+                // the receiver may be claiming `head` right now. whoever gets there first wins.
+                if cursor
+                    .head
+                    .compare_exchange(head, Cursor::EVICTED, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+                {
+                    #[cfg(feature = "debug")]
+                    tracing::info!("Evicting receiver at {head}");
+                    self.num_readers.fetch_sub(1, Ordering::Release);
+                    ring.skip_reads(head, pos, cursor);
+                    return false;
+                }
+            }
+        });
+        tail.stall = Stall::default();
+        self.has_room(tail, n)
+    }
+
+    /// When a sender waiting on a full ring should wake up to evict, with
+    /// [`Eviction::After`].
+    #[cfg(feature = "std")]
+    pub(crate) fn eviction_due(&self, tail: &Tail<T>) -> Option<Instant> {
+        match self.overflow {
+            Overflow::Evict(Eviction::After(timeout)) => tail.stall.since?.checked_add(timeout),
+            _ => None,
+        }
+    }
+
+    /// Wakes the waiting senders once an [`Eviction::After`] stall is due, for the ones
+    /// that can't set an alarm of their own, like async sends. One timer per stall.
+    #[cfg(feature = "std")]
+    pub(crate) fn arm_eviction_alarm(&self) {
+        let due = {
+            let mut tail = self.lock_tail();
+            let Some(due) = self.eviction_due(&tail) else {
+                return;
+            };
+            if core::mem::replace(&mut tail.stall.alarm, true) {
+                return;
+            }
+            due
+        };
+        let waiters = Arc::clone(&self.tx_waiters);
+        std::thread::spawn(move || {
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            waiters.notify_all();
+        });
+    }
+
+    /// Prepares a receiver to block until `deadline`.
+    pub(crate) fn rx_blocker(&self, deadline: Option<Deadline>) -> Blocker<'_> {
+        Blocker::new(
+            &*self.strategy,
+            #[cfg(feature = "std")]
+            &self.rx_waiters,
+            deadline,
+        )
+    }
+
+    /// Prepares a sender to block until `deadline`.
+    pub(crate) fn tx_blocker(&self, deadline: Option<Deadline>) -> Blocker<'_> {
+        Blocker::new(
+            &*self.strategy,
+            #[cfg(feature = "std")]
+            &self.tx_waiters,
+            deadline,
+        )
+    }
+
+    /// Wakes receivers after a send, or after the last channel is gone.
+    pub(crate) fn notify_receivers(&self) {
+        self.rx_waiters.notify_all();
+        self.strategy.notify();
+    }
+
+    /// Wakes every sender, after the last receiver is gone.
+    pub(crate) fn notify_senders(&self) {
+        self.tx_waiters.notify_all();
+        self.strategy.notify();
+    }
+
+    /// Wakes a sender parked on a full ring, after a seat has been freed.
+    pub(crate) fn seat_freed(&self) {
+        self.tx_waiters.notify_one();
+        self.strategy.notify();
+    }
+}
@@ -0,0 +1,259 @@
+//! How blocked senders and receivers wait.
+//!
+//! A [`Channel`](crate::Channel) picks its [`WaitStrategy`] when it's created, with
+//! [`Channel::with_wait_strategy`](crate::Channel::with_wait_strategy). Every handle
+//! spawned from it shares that strategy.
+//!
+//! With the `std` feature, the default is [`Park`]. Without it, the default is [`Spin`].
+
+/// Decides what a blocked [`Channel`](crate::Channel) or [`Receiver`](crate::Receiver)
+/// does while it waits for the other side.
+///
+/// `wait` is called each time a blocking operation finds it can't make progress yet,
+/// after which the operation checks again. `notify` is called whenever the channel
+/// changes in a way that could unblock someone: a send, a read that frees up a seat,
+/// or the last sender or receiver going away.
+///
+/// # Example
+///
+/// A strategy for a core that can sleep until an event, like `WFE`/`SEV` on ARM:
+/// ```
+/// use trotcast::prelude::*;
+///
+/// struct WaitForEvent;
+///
+/// impl WaitStrategy for WaitForEvent {
+///     fn wait(&self, _step: u32) {
+///         // cortex_m::asm::wfe();
+///     }
+///     fn notify(&self) {
+///         // cortex_m::asm::sev();
+///     }
+/// }
+///
+/// let tx = Channel::with_wait_strategy(4, WaitForEvent);
+/// let mut rx = tx.spawn_rx();
+/// tx.send(1).unwrap();
+/// assert_eq!(rx.recv(), Ok(1));
+/// ```
+pub trait WaitStrategy: Send + Sync {
+    /// Wait a little. `step` is 0 on the first call of a blocking operation and goes
+    /// up by one on every call after that.
+    fn wait(&self, step: u32);
+
+    /// Called after the channel changed in a way that could unblock a waiter.
+    fn notify(&self) {}
+
+    /// Whether to park the thread on the channel instead of calling [`WaitStrategy::wait`].
+    ///
+    /// A parked thread is woken by the channel itself, so it never misses a notification.
+    #[cfg(feature = "std")]
+    fn park(&self, step: u32) -> bool {
+        let _ = step;
+        false
+    }
+}
+
+/// Busy-spins until the channel is ready.
+///
+/// The lowest latency, at the cost of a full core per blocked thread.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Spin;
+
+impl WaitStrategy for Spin {
+    fn wait(&self, _step: u32) {
+        core::hint::spin_loop();
+    }
+}
+
+/// Spins for a while, then yields the thread to the scheduler on every step after.
+///
+/// Without the `std` feature there's no scheduler to yield to, so this keeps spinning.
+#[derive(Debug, Clone, Copy)]
+pub struct SpinYield {
+    spins: u32,
+}
+
+impl SpinYield {
+    /// Spin `spins` times before yielding.
+    pub const fn new(spins: u32) -> Self {
+        Self { spins }
+    }
+}
+
+impl Default for SpinYield {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl WaitStrategy for SpinYield {
+    fn wait(&self, step: u32) {
+        if step < self.spins {
+            core::hint::spin_loop();
+            return;
+        }
+        #[cfg(feature = "std")]
+        std::thread::yield_now();
+        #[cfg(not(feature = "std"))]
+        core::hint::spin_loop();
+    }
+}
+
+/// Spins twice as long on every step, up to `2^max_exponent` spins per step.
+///
+/// Keeps the cache line of a contended channel quieter than [`Spin`].
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    max_exponent: u32,
+}
+
+impl Backoff {
+    /// Never spin more than `2^max_exponent` times in a single step.
+    pub const fn new(max_exponent: u32) -> Self {
+        Self { max_exponent }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+impl WaitStrategy for Backoff {
+    fn wait(&self, step: u32) {
+        for _ in 0..1u32 << step.min(self.max_exponent).min(31) {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Parks the thread until the channel wakes it up.
+///
+/// Idle threads use no CPU. Optionally spins a few times first, which saves the cost of
+/// parking when the other side is about to catch up anyway.
+///
+/// Enabled with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Park {
+    spins: u32,
+}
+
+#[cfg(feature = "std")]
+impl Park {
+    /// Park straight away.
+    pub const fn new() -> Self {
+        Self { spins: 0 }
+    }
+
+    /// Spin `spins` times before parking.
+    pub const fn after_spins(spins: u32) -> Self {
+        Self { spins }
+    }
+}
+
+#[cfg(feature = "std")]
+impl WaitStrategy for Park {
+    fn wait(&self, _step: u32) {
+        core::hint::spin_loop();
+    }
+
+    fn park(&self, step: u32) -> bool {
+        step >= self.spins
+    }
+}
+
+/// When a blocking operation gives up. Only exists with the `std` feature.
+#[cfg(feature = "std")]
+pub(crate) type Deadline = std::time::Instant;
+#[cfg(not(feature = "std"))]
+pub(crate) type Deadline = core::convert::Infallible;
+
+/// Drives the channel's [`WaitStrategy`] for a single blocking operation.
+pub(crate) struct Blocker<'a> {
+    strategy: &'a dyn WaitStrategy,
+    step: u32,
+    #[cfg(feature = "std")]
+    waiters: &'a crate::wait::WaitList,
+    #[cfg(feature = "std")]
+    deadline: Option<Deadline>,
+    /// when to stop waiting and look again, without giving up.
+    #[cfg(feature = "std")]
+    alarm: Option<Deadline>,
+    /// whether this operation has been parked on `waiters` before.
+    #[cfg(feature = "std")]
+    parked: bool,
+}
+
+impl<'a> Blocker<'a> {
+    pub(crate) fn new(
+        strategy: &'a dyn WaitStrategy,
+        #[cfg(feature = "std")] waiters: &'a crate::wait::WaitList,
+        deadline: Option<Deadline>,
+    ) -> Self {
+        #[cfg(not(feature = "std"))]
+        let _ = deadline;
+        Self {
+            strategy,
+            step: 0,
+            #[cfg(feature = "std")]
+            waiters,
+            #[cfg(feature = "std")]
+            deadline,
+            #[cfg(feature = "std")]
+            alarm: None,
+            #[cfg(feature = "std")]
+            parked: false,
+        }
+    }
+
+    /// Waits for one step of the strategy, or until `ready` if it parks.
+    ///
+    /// Returns `false` once the deadline has passed.
+    pub(crate) fn wait(&mut self, ready: impl FnMut() -> bool) -> bool {
+        let step = self.step;
+        self.step = self.step.saturating_add(1);
+
+        #[cfg(feature = "std")]
+        {
+            if self.strategy.park(step) {
+                let front = self.parked;
+                self.parked = true;
+                let wake_at = match (self.deadline, self.alarm) {
+                    (Some(deadline), Some(alarm)) => Some(deadline.min(alarm)),
+                    (deadline, alarm) => deadline.or(alarm),
+                };
+                return self.waiters.wait_until(ready, wake_at, front)
+                    || self
+                        .deadline
+                        .is_none_or(|deadline| std::time::Instant::now() < deadline);
+            }
+            if self
+                .deadline
+                .is_some_and(|deadline| std::time::Instant::now() >= deadline)
+            {
+                return false;
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = ready;
+
+        self.strategy.wait(step);
+        true
+    }
+
+    /// Wake up at `alarm` if parked, even if nothing changed. Unlike the deadline,
+    /// [`Blocker::wait`] doesn't give up when it passes.
+    #[cfg(feature = "std")]
+    pub(crate) fn wake_at(&mut self, alarm: Option<Deadline>) {
+        self.alarm = alarm;
+    }
+
+    /// Whether this operation has been parked before, and so was let through in turn.
+    #[cfg(feature = "std")]
+    pub(crate) fn parked(&self) -> bool {
+        self.parked
+    }
+}
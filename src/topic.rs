@@ -0,0 +1,231 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+
+use crate::{
+    mutex::{Mutex, lock},
+    prelude::*,
+};
+
+/// A channel for messages on many topics, like `"orders/eu/fills"`. Each receiver
+/// subscribes to the topics it wants, and only those count it.
+///
+/// Topics are split into levels by `/`. In a pattern, `*` stands for any one level,
+/// and `#` as the last level for any number of them, including none.
+///
+/// Messages are told apart by the [tags](ChannelBuilder::tags) of the channel, one bit
+/// per pattern. Past 64 different patterns, some share a bit. Their receivers still
+/// only get what they subscribed to, but the channel waits for them on each other's
+/// messages too.
+///
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let bus = TopicBus::new(4);
+/// let mut eu_fills = bus.subscribe("orders/eu/fills");
+/// let mut fills = bus.subscribe("orders/*/fills");
+/// let mut orders = bus.subscribe("orders/#");
+///
+/// bus.send("orders/eu/fills", 1).unwrap();
+/// bus.send("orders/us/fills", 2).unwrap();
+/// bus.send("orders/us/cancels", 3).unwrap();
+///
+/// assert_eq!(eu_fills.try_recv(), Ok(("orders/eu/fills", 1)));
+/// assert_eq!(eu_fills.try_recv(), Err(TryRecvError::Empty));
+/// assert_eq!(fills.try_recv(), Ok(("orders/eu/fills", 1)));
+/// assert_eq!(fills.try_recv(), Ok(("orders/us/fills", 2)));
+/// assert_eq!(fills.try_recv(), Err(TryRecvError::Empty));
+/// assert_eq!(orders.try_recv().map(|(_, n)| n), Ok(1));
+/// assert_eq!(orders.try_recv().map(|(_, n)| n), Ok(2));
+/// assert_eq!(orders.try_recv().map(|(_, n)| n), Ok(3));
+///
+/// // nobody is waiting on "orders/eu/fills" anymore, so it never fills up
+/// drop(eu_fills);
+/// drop(orders);
+/// for n in 0..10 {
+///     bus.send("orders/eu/cancels", n).unwrap();
+/// }
+/// ```
+pub struct TopicBus<K, T> {
+    channel: Channel<(K, T)>,
+    patterns: Arc<Patterns>,
+}
+
+impl<K: AsRef<str>, T> TopicBus<K, T> {
+    /// A bus holding up to `capacity` messages, across all topics.
+    pub fn new(capacity: usize) -> Self {
+        Self::from_builder(ChannelBuilder::new(capacity))
+    }
+
+    /// A bus on a channel configured by `builder`. Its [`ChannelBuilder::tags`] are
+    /// replaced by the topics.
+    pub fn from_builder(builder: ChannelBuilder<(K, T)>) -> Self {
+        let patterns = Arc::new(Patterns::default());
+        let tagger = Arc::clone(&patterns);
+        let channel = builder
+            .tags(move |(topic, _): &(K, T)| tagger.tags(topic.as_ref()))
+            .build();
+        Self { channel, patterns }
+    }
+
+    /// Spawns a receiver for the topics matching `pattern`, starting at the next
+    /// message sent.
+    ///
+    /// # Panics
+    /// - if `#` is used anywhere but as the last level
+    pub fn subscribe(&self, pattern: &str) -> TopicReceiver<K, T> {
+        assert!(
+            pattern.split('/').rev().skip(1).all(|level| level != "#"),
+            "`#` can only be the last level of a pattern"
+        );
+        let bit = self.patterns.add(pattern);
+        TopicReceiver {
+            rx: self.channel.spawn_rx_interested(1 << bit),
+            pattern: pattern.into(),
+            patterns: Arc::clone(&self.patterns),
+        }
+    }
+
+    /// Sends `value` on `topic`, to every receiver subscribed to it.
+    ///
+    /// # Errors
+    /// - if there are no receivers at all.
+    /// - if the channel is full.
+    pub fn send(&self, topic: K, value: T) -> Result<(), SendError<(K, T)>> {
+        self.channel.send((topic, value))
+    }
+
+    /// Sends `value` on `topic`, to every receiver subscribed to it. Waits for the
+    /// slowest of them if the channel is full.
+    ///
+    /// # Errors
+    /// - if there are no receivers at all.
+    pub fn blocking_send(&self, topic: K, value: T) -> Result<(), BlockingSendError<(K, T)>> {
+        self.channel.blocking_send((topic, value))
+    }
+}
+
+/// Receives the messages on the topics it subscribed to from a [`TopicBus`].
+pub struct TopicReceiver<K, T> {
+    rx: Receiver<(K, T)>,
+    pattern: String,
+    patterns: Arc<Patterns>,
+}
+
+impl<K: AsRef<str> + Clone, T: Clone> TopicReceiver<K, T> {
+    /// Try to receive a message, along with its topic.
+    ///
+    /// # Errors
+    /// - if there's no new message available
+    /// - if the bus is gone
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    pub fn try_recv(&mut self) -> Result<(K, T), TryRecvError> {
+        loop {
+            let (topic, value) = self.rx.try_recv()?;
+            if matches(&self.pattern, topic.as_ref()) {
+                return Ok((topic, value));
+            }
+        }
+    }
+
+    /// Receive a message, along with its topic. Blocks until one is available.
+    ///
+    /// # Errors
+    /// - if the bus is gone
+    /// - if this receiver fell behind an [`Overflow::Lag`] channel
+    /// - if this receiver was evicted from an [`Overflow::Evict`] channel
+    pub fn recv(&mut self) -> Result<(K, T), RecvError> {
+        loop {
+            let (topic, value) = self.rx.recv()?;
+            if matches(&self.pattern, topic.as_ref()) {
+                return Ok((topic, value));
+            }
+        }
+    }
+}
+
+impl<K, T> TopicReceiver<K, T> {
+    /// The pattern this receiver subscribed with.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl<K, T> Drop for TopicReceiver<K, T> {
+    fn drop(&mut self) {
+        self.patterns.remove(&self.pattern);
+    }
+}
+
+/// The patterns receivers subscribed with, each with one of the 64 bits of the tags.
+/// Past 64 patterns, some share a bit, and their receivers skip what they didn't
+/// subscribe to themselves.
+#[derive(Default)]
+struct Patterns {
+    subscribed: Mutex<Vec<Subscription>>,
+}
+
+struct Subscription {
+    pattern: String,
+    bit: u32,
+    receivers: usize,
+}
+
+impl Patterns {
+    /// Counts a receiver for `pattern`, and returns its bit.
+    fn add(&self, pattern: &str) -> u32 {
+        let mut subscribed = lock(&self.subscribed);
+        if let Some(sub) = subscribed.iter_mut().find(|sub| sub.pattern == pattern) {
+            sub.receivers += 1;
+            return sub.bit;
+        }
+        // the bit fewest other patterns have
+        let bit = (0..u64::BITS)
+            .min_by_key(|&bit| subscribed.iter().filter(|sub| sub.bit == bit).count())
+            .unwrap_or(0);
+        subscribed.push(Subscription {
+            pattern: pattern.into(),
+            bit,
+            receivers: 1,
+        });
+        bit
+    }
+
+    fn remove(&self, pattern: &str) {
+        let mut subscribed = lock(&self.subscribed);
+        if let Some(i) = subscribed.iter().position(|sub| sub.pattern == pattern) {
+            subscribed[i].receivers -= 1;
+            if subscribed[i].receivers == 0 {
+                subscribed.swap_remove(i);
+            }
+        }
+    }
+
+    /// The bits of every pattern `topic` matches.
+    fn tags(&self, topic: &str) -> u64 {
+        lock(&self.subscribed)
+            .iter()
+            .filter(|sub| matches(&sub.pattern, topic))
+            .fold(0, |tags, sub| tags | 1 << sub.bit)
+    }
+}
+
+/// Whether `topic` matches `pattern`, level by level.
+fn matches(pattern: &str, topic: &str) -> bool {
+    let mut levels = topic.split('/');
+    for part in pattern.split('/') {
+        match part {
+            "#" => return true,
+            "*" => {
+                if levels.next().is_none() {
+                    return false;
+                }
+            }
+            _ => {
+                if levels.next() != Some(part) {
+                    return false;
+                }
+            }
+        }
+    }
+    levels.next().is_none()
+}
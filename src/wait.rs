@@ -0,0 +1,210 @@
+use core::{
+    sync::atomic::{self, AtomicUsize, Ordering},
+    task::Waker,
+};
+
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicBool;
+#[cfg(feature = "std")]
+use std::{
+    thread::{self, Thread},
+    time::Instant,
+};
+
+use crate::mutex::{Mutex, lock};
+
+/// Something waiting on a [`WaitList`].
+struct Waiter {
+    id: usize,
+    wake: Wake,
+}
+
+/// How to wake a [`Waiter`].
+enum Wake {
+    /// A thread parked in [`WaitList::wait_until`].
+    #[cfg(feature = "std")]
+    Thread {
+        thread: Thread,
+        notified: Arc<AtomicBool>,
+    },
+    /// A task that registered with [`WaitList::register`].
+    Task(Waker),
+}
+
+impl Wake {
+    fn wake(self) {
+        match self {
+            #[cfg(feature = "std")]
+            Wake::Thread { thread, notified } => {
+                notified.store(true, Ordering::Release);
+                thread.unpark();
+            }
+            Wake::Task(waker) => waker.wake(),
+        }
+    }
+}
+
+/// A list of threads and tasks that are waiting until the state of the channel changes.
+///
+/// Waiters register themselves, re-check their condition, and only then go to sleep.
+/// Notifiers change the state of the channel first and only then check for waiters,
+/// so a wakeup can't be lost in between.
+pub(crate) struct WaitList {
+    waiters: Mutex<VecDeque<Waiter>>,
+    /// number of registered waiters. Lets notifiers skip the lock when nobody is waiting.
+    len: AtomicUsize,
+    next_id: AtomicUsize,
+}
+
+impl WaitList {
+    pub(crate) fn new() -> Self {
+        Self {
+            waiters: Mutex::new(VecDeque::new()),
+            len: AtomicUsize::new(0),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, wake: Wake, front: bool) -> usize {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut waiters = lock(&self.waiters);
+            let waiter = Waiter { id, wake };
+            if front {
+                waiters.push_front(waiter);
+            } else {
+                waiters.push_back(waiter);
+            }
+            self.len.store(waiters.len(), Ordering::Relaxed);
+        }
+        // pairs with the fence in `notify_all` and `notify_one`
+        atomic::fence(Ordering::SeqCst);
+        id
+    }
+
+    /// Parks the current thread until `ready` returns true.
+    ///
+    /// Waiters are woken by [`WaitList::notify_one`] in the order they registered.
+    /// Pass `front` when the caller was already woken once but lost its turn,
+    /// so it goes back to the front of the line instead of the back.
+    ///
+    /// Returns `false` if `deadline` passed before that happened.
+    #[cfg(feature = "std")]
+    pub(crate) fn wait_until(
+        &self,
+        mut ready: impl FnMut() -> bool,
+        deadline: Option<Instant>,
+        mut front: bool,
+    ) -> bool {
+        loop {
+            if ready() {
+                return true;
+            }
+            let notified = Arc::new(AtomicBool::new(false));
+            let id = self.push(
+                Wake::Thread {
+                    thread: thread::current(),
+                    notified: Arc::clone(&notified),
+                },
+                front,
+            );
+
+            if ready() {
+                self.remove(id);
+                return true;
+            }
+
+            while !notified.load(Ordering::Acquire) {
+                match deadline {
+                    None => thread::park(),
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            if !self.remove(id) {
+                                // don't swallow a wakeup meant for whoever is next in line
+                                self.notify_one();
+                            }
+                            return ready();
+                        }
+                        thread::park_timeout(deadline - now);
+                    }
+                }
+            }
+            front = true;
+        }
+    }
+
+    /// Registers `waker` to be woken by the next notification.
+    ///
+    /// `id` keeps track of the registration between polls, so a task that is polled
+    /// again replaces its old waker instead of adding another one. A task that was
+    /// already woken once goes back to the front of the line. The caller must
+    /// check its condition again after this, before returning `Poll::Pending`.
+    pub(crate) fn register(&self, id: &mut Option<usize>, waker: &Waker) {
+        let front = self.unregister(id);
+        *id = Some(self.push(Wake::Task(waker.clone()), front));
+    }
+
+    /// Drops the registration made by [`WaitList::register`], if it's still there.
+    ///
+    /// Returns `true` if the task had been woken through it.
+    pub(crate) fn unregister(&self, id: &mut Option<usize>) -> bool {
+        match id.take() {
+            Some(id) => !self.remove(id),
+            None => false,
+        }
+    }
+
+    /// Removes the waiter. Returns `false` if it had already been notified.
+    fn remove(&self, id: usize) -> bool {
+        let mut waiters = lock(&self.waiters);
+        let len = waiters.len();
+        waiters.retain(|waiter| waiter.id != id);
+        self.len.store(waiters.len(), Ordering::Relaxed);
+        waiters.len() != len
+    }
+
+    /// Wakes whoever has been waiting the longest.
+    pub(crate) fn notify_one(&self) {
+        // pairs with the fence in `push`
+        atomic::fence(Ordering::SeqCst);
+        if self.len.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let waiter = {
+            let mut waiters = lock(&self.waiters);
+            let waiter = waiters.pop_front();
+            self.len.store(waiters.len(), Ordering::Relaxed);
+            waiter
+        };
+        if let Some(waiter) = waiter {
+            waiter.wake.wake();
+        }
+    }
+
+    /// Wakes everyone.
+    pub(crate) fn notify_all(&self) {
+        // pairs with the fence in `push`
+        atomic::fence(Ordering::SeqCst);
+        if self.len.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let waiters = {
+            let mut waiters = lock(&self.waiters);
+            self.len.store(0, Ordering::Relaxed);
+            core::mem::take(&mut *waiters)
+        };
+        for waiter in waiters {
+            waiter.wake.wake();
+        }
+    }
+
+    /// The number of waiters.
+    #[cfg(feature = "debug")]
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,228 @@
+use core::{fmt, ops::Deref, sync::atomic::Ordering};
+
+use crate::{
+    mutex::{Mutex, RwLockReadGuard, lock},
+    prelude::*,
+};
+
+/// A channel that only keeps the newest value, for state like config or a leader id.
+///
+/// Sending never waits for receivers and replaces whatever was there. Receivers always
+/// get the newest value, skipping any that were replaced before they looked.
+///
+/// ```
+/// use trotcast::prelude::*;
+///
+/// let leader = Watch::new("a");
+/// let mut rx = leader.subscribe();
+/// assert_eq!(*rx.borrow(), "a");
+///
+/// leader.send("b");
+/// leader.send("c");
+/// // "b" was replaced before anyone looked
+/// assert_eq!(rx.recv_latest(), Ok("c"));
+///
+/// let handle = std::thread::spawn(move || {
+///     rx.changed().unwrap();
+///     let seen = *rx.borrow();
+///     (seen, rx.changed())
+/// });
+/// leader.send("d");
+/// drop(leader);
+/// assert_eq!(handle.join().unwrap(), ("d", Err(RecvError::Disconnected)));
+/// ```
+pub struct Watch<T> {
+    channel: Channel<T>,
+    /// keeps the channel open, so the value is stored even with nobody watching.
+    /// with `Overflow::Lag`, it never holds anything up.
+    keeper: Receiver<T>,
+    /// makes `send_if_modified` a single step.
+    sending: Mutex<()>,
+}
+
+impl<T: Clone> Watch<T> {
+    /// A watch holding `initial`.
+    pub fn new(initial: T) -> Self {
+        let channel = ChannelBuilder::new(1)
+            .overflow(Overflow::Lag)
+            .wait_for_reads()
+            .build();
+        let keeper = channel.spawn_rx();
+        // can't fail. there's a receiver, and the channel never fills up.
+        let _ = channel.send(initial);
+        Self {
+            channel,
+            keeper,
+            sending: Mutex::new(()),
+        }
+    }
+
+    /// Spawns a receiver. The current value counts as seen.
+    pub fn subscribe(&self) -> WatchReceiver<T> {
+        WatchReceiver {
+            rx: self.channel.spawn_rx(),
+        }
+    }
+
+    /// Replaces the value and wakes every receiver.
+    ///
+    /// This doesn't wait for receivers to catch up, only for a [`WatchRef`] of the
+    /// value before the current one to be dropped.
+    pub fn send(&self, value: T) {
+        let _sending = lock(&self.sending);
+        self.publish(value);
+    }
+
+    /// Calls `modify` on a copy of the current value, and sends it if `modify` returns
+    /// `true`. Otherwise nothing is sent, and nobody is woken.
+    ///
+    /// Returns whether the value was sent.
+    ///
+    /// ```
+    /// use trotcast::prelude::*;
+    ///
+    /// let healthy = Watch::new(true);
+    /// let rx = healthy.subscribe();
+    ///
+    /// let set = |value| move |current: &mut bool| std::mem::replace(current, value) != value;
+    /// assert!(!healthy.send_if_modified(set(true)));
+    /// assert!(!rx.has_changed());
+    ///
+    /// assert!(healthy.send_if_modified(set(false)));
+    /// assert!(rx.has_changed());
+    /// ```
+    pub fn send_if_modified(&self, modify: impl FnOnce(&mut T) -> bool) -> bool {
+        let _sending = lock(&self.sending);
+        let mut value = self.borrow().clone();
+        if !modify(&mut value) {
+            return false;
+        }
+        self.publish(value);
+        true
+    }
+
+    /// The current value.
+    pub fn borrow(&self) -> WatchRef<'_, T> {
+        WatchRef::latest(&self.keeper)
+    }
+
+    /// Sends `value`, with `sending` held.
+    fn publish(&self, value: T) {
+        // wait for a `WatchRef` of the value this replaces here, rather than in the
+        // channel with the tail locked. nobody else sends in the meantime.
+        let tail = self.keeper.shared.tail.load(Ordering::Acquire);
+        drop(self.keeper.ring.seat(tail).write_lock());
+        // can't fail, see `Watch::new`
+        let _ = self.channel.send(value);
+    }
+}
+
+/// Receives from a [`Watch`].
+pub struct WatchReceiver<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> WatchReceiver<T> {
+    /// Whether a value was sent that this receiver hasn't seen yet.
+    pub fn has_changed(&self) -> bool {
+        self.rx.shared.tail.load(Ordering::Acquire) != self.rx.head
+    }
+
+    /// Waits for a value this receiver hasn't seen, and marks it seen.
+    ///
+    /// # Errors
+    /// - if the [`Watch`] is gone, and every value has been seen
+    pub fn changed(&mut self) -> Result<(), RecvError> {
+        self.wait()?;
+        let tail = self.rx.shared.tail.load(Ordering::Acquire);
+        self.see(tail);
+        Ok(())
+    }
+
+    /// The newest value, without marking it seen.
+    pub fn borrow(&self) -> WatchRef<'_, T> {
+        WatchRef::latest(&self.rx)
+    }
+
+    fn see(&mut self, head: usize) {
+        self.rx.head = head;
+        self.rx.cursor.head.store(head, Ordering::Release);
+    }
+
+    fn wait(&self) -> Result<(), RecvError> {
+        let shared = &*self.rx.shared;
+        let mut blocker = shared.rx_blocker(None);
+        loop {
+            if self.has_changed() {
+                return Ok(());
+            }
+            if shared.num_writers.load(Ordering::Relaxed) == 0 {
+                // a last value might have been sent in between
+                if self.has_changed() {
+                    return Ok(());
+                }
+                return Err(RecvError::Disconnected);
+            }
+            blocker.wait(|| self.rx.is_ready());
+        }
+    }
+}
+
+impl<T: Clone> WatchReceiver<T> {
+    /// Waits for a value this receiver hasn't seen, and returns the newest one.
+    ///
+    /// # Errors
+    /// - if the [`Watch`] is gone, and every value has been seen
+    pub fn recv_latest(&mut self) -> Result<T, RecvError> {
+        self.wait()?;
+        let latest = WatchRef::latest(&self.rx);
+        let (value, pos) = (latest.clone(), latest.pos);
+        drop(latest);
+        self.see(pos + 1);
+        Ok(value)
+    }
+}
+
+/// A value of a [`Watch`], borrowed from the channel.
+///
+/// The [`Watch`] can send one more value while this is held, and then waits for it
+/// to be dropped. Don't hold on to it for long.
+pub struct WatchRef<'a, T> {
+    value: &'a T,
+    pos: usize,
+    _lock: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a, T> WatchRef<'a, T> {
+    fn latest(rx: &'a Receiver<T>) -> Self {
+        loop {
+            // a watch starts out with a value, so there's always one
+            let pos = rx.shared.tail.load(Ordering::Acquire) - 1;
+            let seat = rx.ring.seat(pos);
+            let lock = seat.read_lock();
+            let state = unsafe { &*seat.state.get() };
+            // otherwise it was replaced after we loaded the tail
+            if state.pos == pos {
+                return Self {
+                    value: state.val.as_ref().expect("watch values are never taken"),
+                    pos,
+                    _lock: lock,
+                };
+            }
+        }
+    }
+}
+
+impl<T> Deref for WatchRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for WatchRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::{CountParks, within};
+use trotcast::prelude::*;
+
+/// Receivers finishing the same seat at the same time must still wake the sender.
+#[test]
+fn concurrent_last_reads_wake_sender() {
+    within(Duration::from_secs(60), || {
+        for _ in 0..500 {
+            let tx = Channel::new(1);
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let mut rx = tx.spawn_rx();
+                    thread::spawn(move || {
+                        let mut received = 0;
+                        while rx.recv().is_ok() {
+                            received += 1;
+                        }
+                        received
+                    })
+                })
+                .collect();
+            for i in 0..200 {
+                tx.blocking_send(i).unwrap();
+            }
+            drop(tx);
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), 200);
+            }
+        }
+    });
+}
+
+/// Parked senders get their turn in the order they started waiting.
+#[test]
+fn parked_senders_wake_in_order() {
+    within(Duration::from_secs(10), || {
+        let strategy = CountParks::default();
+        let tx = Channel::with_wait_strategy(1, strategy.clone());
+        let mut rx = tx.spawn_rx();
+        tx.send(0).unwrap();
+
+        let senders: Vec<_> = (1..=3)
+            .map(|i| {
+                let tx = tx.clone();
+                let handle = thread::spawn(move || tx.blocking_send(i).unwrap());
+                strategy.wait_for(i);
+                handle
+            })
+            .collect();
+
+        for i in 0..=3 {
+            assert_eq!(rx.recv(), Ok(i));
+        }
+        for handle in senders {
+            handle.join().unwrap();
+        }
+    });
+}
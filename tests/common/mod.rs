@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    task::{Context, Poll, Wake},
+    thread,
+    time::Duration,
+};
+
+use trotcast::prelude::*;
+
+/// Runs `f` on its own thread, and fails if it hasn't finished within `timeout`.
+pub fn within<R: Send + 'static>(timeout: Duration, f: impl FnOnce() -> R + Send + 'static) -> R {
+    let (done, finished) = mpsc::channel();
+    thread::spawn(move || done.send(f()).unwrap());
+    finished.recv_timeout(timeout).expect("deadlocked")
+}
+
+/// Polls `fut` on this thread until it's done.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Unparker(thread::Thread);
+    impl Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark()
+        }
+    }
+    let waker = Arc::new(Unparker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(out) => return out,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Parks straight away like [`Park`], and counts how often it did.
+#[derive(Clone, Default)]
+pub struct CountParks(Arc<AtomicUsize>);
+
+impl CountParks {
+    pub fn parked(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Waits until threads have parked `n` times.
+    pub fn wait_for(&self, n: usize) {
+        while self.parked() < n {
+            thread::yield_now();
+        }
+        // give the last one time to get in line
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+impl WaitStrategy for CountParks {
+    fn wait(&self, _step: u32) {}
+
+    fn park(&self, _step: u32) -> bool {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+}
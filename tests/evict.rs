@@ -0,0 +1,159 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::{block_on, within};
+use trotcast::prelude::*;
+
+/// A receiver that never reads can't hold up a blocked sender for good.
+#[test]
+fn blocking_send_evicts_after_failed_sends() {
+    within(Duration::from_secs(10), || {
+        let tx = Channel::builder(1)
+            .overflow(Overflow::Evict(Eviction::FailedSends(3)))
+            .build();
+        let mut stuck = tx.spawn_rx();
+        let mut live = tx.spawn_rx();
+
+        tx.send(0).unwrap();
+        assert_eq!(live.recv(), Ok(0));
+        assert_eq!(tx.send(1), Err(SendError::Full(1)));
+        assert_eq!(tx.send(1), Err(SendError::Full(1)));
+        // the third send in a row that finds it full
+        tx.blocking_send(1).unwrap();
+        assert_eq!(live.recv(), Ok(1));
+        assert_eq!(stuck.recv(), Err(RecvError::Evicted));
+    });
+}
+
+#[test]
+fn send_async_evicts_after_failed_sends() {
+    within(Duration::from_secs(10), || {
+        let tx = Channel::builder(1)
+            .overflow(Overflow::Evict(Eviction::FailedSends(3)))
+            .build();
+        let mut stuck = tx.spawn_rx();
+        let mut live = tx.spawn_rx();
+
+        tx.send(0).unwrap();
+        assert_eq!(live.recv(), Ok(0));
+        assert_eq!(tx.send(1), Err(SendError::Full(1)));
+        assert_eq!(tx.send(1), Err(SendError::Full(1)));
+        block_on(tx.send_async(1)).unwrap();
+        assert_eq!(live.recv(), Ok(1));
+        assert_eq!(stuck.recv(), Err(RecvError::Evicted));
+    });
+}
+
+#[test]
+fn send_async_evicts_after_timeout() {
+    within(Duration::from_secs(10), || {
+        let tx = Channel::builder(1)
+            .overflow(Overflow::Evict(Eviction::After(Duration::from_millis(50))))
+            .build();
+        let mut stuck = tx.spawn_rx();
+        let mut live = tx.spawn_rx();
+
+        tx.send(0).unwrap();
+        assert_eq!(live.recv(), Ok(0));
+        block_on(tx.send_async(1)).unwrap();
+        assert_eq!(live.recv(), Ok(1));
+        assert_eq!(stuck.recv(), Err(RecvError::Evicted));
+    });
+}
+
+/// Waiting on a slow receiver counts once per send, not once per check.
+#[test]
+fn slow_receiver_is_not_evicted() {
+    within(Duration::from_secs(10), || {
+        let tx = Channel::builder(1)
+            .overflow(Overflow::Evict(Eviction::FailedSends(1000)))
+            .build();
+        let mut rx = tx.spawn_rx();
+        let reader = thread::spawn(move || {
+            let mut received = 0;
+            while rx.recv().is_ok() {
+                thread::sleep(Duration::from_millis(1));
+                received += 1;
+            }
+            received
+        });
+        for i in 0..100 {
+            tx.blocking_send(i).unwrap();
+        }
+        drop(tx);
+        assert_eq!(reader.join().unwrap(), 100);
+    });
+}
+
+#[test]
+fn blocking_send_evicts_after_timeout() {
+    within(Duration::from_secs(10), || {
+        let tx = Channel::builder(1)
+            .overflow(Overflow::Evict(Eviction::After(Duration::from_millis(50))))
+            .build();
+        let mut stuck = tx.spawn_rx();
+        let mut live = tx.spawn_rx();
+
+        tx.send(0).unwrap();
+        assert_eq!(live.recv(), Ok(0));
+        tx.blocking_send(1).unwrap();
+        assert_eq!(live.recv(), Ok(1));
+        assert_eq!(stuck.try_recv(), Err(TryRecvError::Evicted));
+    });
+}
+
+/// Receivers that keep up are never evicted, however long senders are blocked on
+/// each other.
+#[test]
+fn keeping_up_is_never_evicted() {
+    within(Duration::from_secs(30), || {
+        let tx = Channel::builder(4)
+            .overflow(Overflow::Evict(Eviction::After(Duration::from_secs(5))))
+            .build();
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let mut rx = tx.spawn_rx();
+                thread::spawn(move || rx.iter().count())
+            })
+            .collect();
+        let senders: Vec<_> = (0..4)
+            .map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.blocking_send(i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        drop(tx);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 4000);
+        }
+    });
+}
+
+#[test]
+fn evicted_receiver_can_resubscribe() {
+    let tx = Channel::builder(1)
+        .overflow(Overflow::Evict(Eviction::FailedSends(1)))
+        .build();
+    let mut stuck = tx.spawn_rx();
+    let mut live = tx.spawn_rx();
+
+    tx.send(0).unwrap();
+    assert_eq!(live.recv(), Ok(0));
+    tx.send(1).unwrap();
+    assert_eq!(live.recv(), Ok(1));
+    assert!(stuck.evicted());
+
+    stuck.resubscribe();
+    assert!(!stuck.evicted());
+    tx.send(2).unwrap();
+    assert_eq!(stuck.recv(), Ok(2));
+    assert_eq!(live.recv(), Ok(2));
+}
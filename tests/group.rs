@@ -0,0 +1,97 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::within;
+use trotcast::prelude::*;
+
+/// Every group sees every message, and within a group each one is handled once.
+#[test]
+fn each_group_handles_each_message_once() {
+    within(Duration::from_secs(30), || {
+        let tx = Channel::new(4);
+        let workers: Vec<_> = ["billing", "audit"]
+            .into_iter()
+            .flat_map(|group| (0..3).map(|_| tx.join_group(group)).collect::<Vec<_>>())
+            .collect();
+        let handles: Vec<_> = workers
+            .into_iter()
+            .map(|mut worker| {
+                thread::spawn(move || {
+                    let mut handled = vec![];
+                    while let Ok(&msg) = worker.recv() {
+                        handled.push(msg);
+                    }
+                    (worker.group().to_string(), handled)
+                })
+            })
+            .collect();
+
+        for i in 0..2000 {
+            tx.blocking_send(i).unwrap();
+        }
+        drop(tx);
+
+        let mut billing = vec![];
+        let mut audit = vec![];
+        for handle in handles {
+            let (group, handled) = handle.join().unwrap();
+            match group.as_str() {
+                "billing" => billing.extend(handled),
+                _ => audit.extend(handled),
+            }
+        }
+        billing.sort();
+        audit.sort();
+        assert_eq!(billing, (0..2000).collect::<Vec<_>>());
+        assert_eq!(audit, (0..2000).collect::<Vec<_>>());
+    });
+}
+
+/// Members that go away in the middle of a message hand it on.
+#[test]
+fn dropped_members_hand_on_their_message() {
+    within(Duration::from_secs(30), || {
+        let tx = Channel::new(4);
+        let mut steady = tx.join_group("workers");
+        let quitters: Vec<_> = (0..10)
+            .map(|_| {
+                let mut quitter = tx.join_group("workers");
+                thread::spawn(move || {
+                    // claims a message, and leaves without finishing it
+                    _ = quitter.recv();
+                })
+            })
+            .collect();
+
+        let sender = thread::spawn(move || {
+            for i in 0..100 {
+                tx.blocking_send(i).unwrap();
+            }
+        });
+        // once they're all gone, what they claimed is waiting for `steady`
+        for quitter in quitters {
+            quitter.join().unwrap();
+        }
+        let mut handled = vec![];
+        while let Ok(&msg) = steady.recv() {
+            handled.push(msg);
+        }
+        sender.join().unwrap();
+        handled.sort();
+        assert_eq!(handled, (0..100).collect::<Vec<_>>());
+    });
+}
+
+/// A group counts as one receiver, so a member that keeps up is enough.
+#[test]
+fn one_member_keeps_the_group_going() {
+    let tx = Channel::new(2);
+    let mut worker = tx.join_group("workers");
+    let _idle = tx.join_group("workers");
+    for i in 0..10 {
+        tx.send(i).unwrap();
+        assert_eq!(worker.try_recv(), Ok(&i));
+    }
+    worker.done();
+}
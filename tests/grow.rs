@@ -0,0 +1,77 @@
+mod common;
+
+use std::{sync::Arc, thread, time::Duration};
+
+use common::within;
+use trotcast::prelude::*;
+
+/// Senders never wait on an unbounded channel, however far behind a receiver is.
+#[test]
+fn slow_receiver_gets_everything_in_order() {
+    let tx = Channel::unbounded();
+    let mut rx = tx.spawn_rx();
+    for i in 0..10_000 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(
+        rx.try_iter().collect::<Vec<_>>(),
+        (0..10_000).collect::<Vec<_>>()
+    );
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn concurrent_senders_and_receivers() {
+    within(Duration::from_secs(30), || {
+        let tx: Channel<(usize, u32)> = Channel::builder(4).overflow(Overflow::Grow).build();
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let mut rx = tx.spawn_rx();
+                thread::spawn(move || {
+                    let mut last = [None; 4];
+                    let mut received = 0;
+                    for (sender, i) in rx.iter() {
+                        // each sender's messages arrive in the order it sent them
+                        assert!(last[sender].is_none_or(|last| last < i));
+                        last[sender] = Some(i);
+                        received += 1;
+                    }
+                    received
+                })
+            })
+            .collect();
+        let senders: Vec<_> = (0..4)
+            .map(|sender| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..2000 {
+                        tx.send((sender, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        drop(tx);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 8000);
+        }
+    });
+}
+
+/// Segments every receiver has read past are freed.
+#[test]
+fn read_segments_are_freed() {
+    let tx = Channel::builder(4).overflow(Overflow::Grow).build();
+    let mut rx = tx.spawn_rx();
+    let payload = Arc::new(());
+    for _ in 0..100 {
+        tx.send(Arc::clone(&payload)).unwrap();
+    }
+    assert_eq!(Arc::strong_count(&payload), 101);
+
+    while rx.try_recv().is_ok() {}
+    // only the segment `rx` is in can still hold on to messages
+    assert!(Arc::strong_count(&payload) <= 1 + 4);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::within;
+use trotcast::prelude::*;
+
+fn tagged_channel(capacity: usize) -> Channel<u64> {
+    // the message is its own tags
+    Channel::builder(capacity).tags(|msg: &u64| *msg).build()
+}
+
+/// A receiver with only unwanted messages pending isn't ready.
+#[test]
+fn select_skips_unwanted_messages() {
+    let tx = tagged_channel(4);
+    let mut rx = tx.spawn_rx_interested(0b10);
+    tx.send(0b01).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    assert_eq!(sel.try_select(), Err(TrySelectError::Empty));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    tx.send(0b01).unwrap();
+    tx.send(0b10).unwrap();
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    assert_eq!(sel.try_select(), Ok(0));
+    assert_eq!(rx.try_recv(), Ok(0b10));
+}
+
+#[test]
+fn select_waits_for_wanted_message() {
+    within(Duration::from_secs(10), || {
+        let tx = tagged_channel(8);
+        let mut rx = tx.spawn_rx_interested(0b10);
+        let _everything = tx.spawn_rx();
+
+        let sender = thread::spawn({
+            let tx = tx.clone();
+            move || {
+                for _ in 0..3 {
+                    tx.send(0b01).unwrap();
+                    thread::sleep(Duration::from_millis(10));
+                }
+                tx.send(0b11).unwrap();
+            }
+        });
+        let index = {
+            let mut sel = Select::new();
+            sel.recv(&rx);
+            sel.select()
+        };
+        assert_eq!(index, 0);
+        assert_eq!(rx.try_recv(), Ok(0b11));
+        sender.join().unwrap();
+    });
+}
+
+/// Filtered receivers get exactly what they asked for, and aren't waited on for the rest.
+#[test]
+fn filtered_receivers_under_load() {
+    within(Duration::from_secs(30), || {
+        let tx = tagged_channel(4);
+        let handles: Vec<_> = [0b01, 0b10, 0b11]
+            .into_iter()
+            .map(|interest| {
+                let mut rx = tx.spawn_rx_interested(interest);
+                thread::spawn(move || {
+                    let received: Vec<_> = rx.iter().collect();
+                    assert!(received.iter().all(|msg| msg & interest != 0));
+                    received.len()
+                })
+            })
+            .collect();
+
+        for i in 0..3000 {
+            tx.blocking_send(1 << (i % 2)).unwrap();
+        }
+        drop(tx);
+        let received: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(received, [1500, 1500, 3000]);
+    });
+}
+
+/// `len` counts what the receiver will actually get.
+#[test]
+fn len_leaves_out_unwanted_messages() {
+    let tx = tagged_channel(8);
+    let mut rx = tx.spawn_rx_interested(0b10);
+    tx.send(0b01).unwrap();
+    assert_eq!((rx.len(), rx.lag()), (0, 1));
+    assert!(rx.is_empty());
+
+    for msg in [0b10, 0b01, 0b11] {
+        tx.send(msg).unwrap();
+    }
+    assert_eq!((rx.len(), rx.lag()), (2, 4));
+    assert!(!rx.is_empty());
+    assert_eq!(rx.try_recv(), Ok(0b10));
+    assert_eq!(rx.len(), 1);
+}
@@ -0,0 +1,38 @@
+use trotcast::prelude::*;
+
+/// Without history nothing is kept for a paused receiver, even with `Overflow::Lag`.
+#[test]
+fn oldest_without_history_resumes_at_the_tail() {
+    let tx = Channel::builder(4).overflow(Overflow::Lag).build();
+    let mut live = tx.spawn_rx();
+    let paused = tx.spawn_rx().pause();
+    for i in 0..2 {
+        tx.send(i).unwrap();
+        assert_eq!(live.recv(), Ok(i));
+    }
+
+    let (mut rx, skipped) = paused.resume(ResumeFrom::Oldest);
+    assert_eq!(skipped, 2);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.send(2).unwrap();
+    assert_eq!(rx.recv(), Ok(2));
+}
+
+#[test]
+fn oldest_with_history_resumes_at_the_history() {
+    let tx = Channel::builder(2)
+        .overflow(Overflow::Lag)
+        .history(2)
+        .build();
+    let mut live = tx.spawn_rx();
+    let paused = tx.spawn_rx().pause();
+    for i in 0..6 {
+        tx.send(i).unwrap();
+        assert_eq!(live.recv(), Ok(i));
+    }
+
+    let (mut rx, skipped) = paused.resume(ResumeFrom::Oldest);
+    assert_eq!(skipped, 4);
+    assert_eq!(rx.recv(), Ok(4));
+    assert_eq!(rx.recv(), Ok(5));
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::{CountParks, within};
+use trotcast::prelude::*;
+
+/// An idle receiver parks once, and stays parked until something is sent.
+#[test]
+fn idle_receiver_parks_until_send() {
+    within(Duration::from_secs(10), || {
+        let strategy = CountParks::default();
+        let tx = Channel::with_wait_strategy(4, strategy.clone());
+        let mut rx = tx.spawn_rx();
+        let handle = thread::spawn(move || rx.recv());
+
+        strategy.wait_for(1);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(strategy.parked(), 1, "the receiver spun instead of parking");
+
+        tx.send(7).unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(7));
+    });
+}
+
+#[test]
+fn dropping_the_channel_wakes_parked_receivers() {
+    within(Duration::from_secs(10), || {
+        let strategy = CountParks::default();
+        let tx = Channel::<u32>::with_wait_strategy(4, strategy.clone());
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let mut rx = tx.spawn_rx();
+                thread::spawn(move || rx.recv())
+            })
+            .collect();
+
+        strategy.wait_for(3);
+        drop(tx);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Err(RecvError::Disconnected));
+        }
+    });
+}
+
+/// With the default strategy, every parked receiver is woken by each send.
+#[test]
+fn parked_receivers_get_every_message() {
+    within(Duration::from_secs(30), || {
+        let tx = Channel::new(2);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut rx = tx.spawn_rx();
+                thread::spawn(move || rx.iter().collect::<Vec<_>>())
+            })
+            .collect();
+
+        for i in 0..1000 {
+            tx.blocking_send(i).unwrap();
+            if i % 100 == 0 {
+                // let the receivers catch up and park again
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        drop(tx);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (0..1000).collect::<Vec<_>>());
+        }
+    });
+}
+
+/// Everything sent before `drain` counts as read, even what the iterator didn't yield.
+#[test]
+fn drain_takes_everything_sent_so_far() {
+    let tx = Channel::new(4);
+    let mut rx = tx.spawn_rx();
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+    let mut drain = rx.drain();
+    assert_eq!(drain.len(), 4);
+    assert_eq!(drain.next(), Some(0));
+    drop(drain);
+
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    // the seats are free again
+    for i in 4..8 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(rx.drain().collect::<Vec<_>>(), [4, 5, 6, 7]);
+}
+
+#[test]
+fn drain_skips_what_lag_wrote_over() {
+    let tx = Channel::builder(2).overflow(Overflow::Lag).build();
+    let mut rx = tx.spawn_rx();
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(rx.drain().collect::<Vec<_>>(), [3, 4]);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use common::within;
+use trotcast::prelude::*;
+
+fn lag_channel(capacity: usize) -> Channel<u32> {
+    Channel::builder(capacity).overflow(Overflow::Lag).build()
+}
+
+/// Everything still pending, skipping over what was missed.
+fn received(rx: &mut Receiver<u32>) -> Vec<u32> {
+    let mut received = vec![];
+    loop {
+        match rx.try_recv() {
+            Ok(msg) => received.push(msg),
+            Err(TryRecvError::Lagged(_)) => {}
+            Err(_) => return received,
+        }
+    }
+}
+
+/// With `Overflow::Lag`, a held `RecvRef` doesn't hold up sends on the same thread.
+#[test]
+fn lag_sends_past_a_held_recv_ref() {
+    within(Duration::from_secs(10), || {
+        let tx = lag_channel(2);
+        let mut rx = tx.spawn_rx();
+        tx.send(0).unwrap();
+
+        let msg = rx.recv_ref().unwrap();
+        for i in 1..=10 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(
+            *msg, 0,
+            "the message was written over while it was borrowed"
+        );
+        drop(msg);
+
+        assert_eq!(received(&mut rx).last(), Some(&10));
+    });
+}
+
+/// ...nor sends from other threads.
+#[test]
+fn lag_sends_from_other_threads_past_a_held_recv_ref() {
+    within(Duration::from_secs(10), || {
+        let tx = lag_channel(2);
+        let mut rx = tx.spawn_rx();
+        tx.send(0).unwrap();
+
+        let (borrowed, release) = (mpsc::channel(), mpsc::channel::<()>());
+        let reader = thread::spawn(move || {
+            let msg = rx.recv_ref().unwrap();
+            borrowed.0.send(()).unwrap();
+            release.1.recv().unwrap();
+            assert_eq!(
+                *msg, 0,
+                "the message was written over while it was borrowed"
+            );
+            drop(msg);
+            received(&mut rx)
+        });
+
+        borrowed.1.recv().unwrap();
+        let sender = thread::spawn(move || {
+            for i in 1..=100 {
+                tx.send(i).unwrap();
+            }
+        });
+        sender.join().unwrap();
+        release.0.send(()).unwrap();
+        assert_eq!(reader.join().unwrap().last(), Some(&100));
+    });
+}
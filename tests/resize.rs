@@ -0,0 +1,109 @@
+mod common;
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use common::{CountParks, within};
+use trotcast::prelude::*;
+
+/// Making room wakes a sender parked on the full ring.
+#[test]
+fn growing_wakes_blocked_sender() {
+    within(Duration::from_secs(10), || {
+        let strategy = CountParks::default();
+        let tx = Channel::with_wait_strategy(1, strategy.clone());
+        let mut rx = tx.spawn_rx();
+        tx.send(0).unwrap();
+
+        let sender = thread::spawn({
+            let tx = tx.clone();
+            move || tx.blocking_send(1).unwrap()
+        });
+        strategy.wait_for(1);
+        tx.set_capacity(2).unwrap();
+        sender.join().unwrap();
+
+        assert_eq!(rx.recv(), Ok(0));
+        assert_eq!(rx.recv(), Ok(1));
+    });
+}
+
+/// Resizing over and over while in use loses and reorders nothing.
+#[test]
+fn resizing_under_load() {
+    within(Duration::from_secs(30), || {
+        let tx: Channel<(usize, u32)> = Channel::new(4);
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let mut rx = tx.spawn_rx();
+                thread::spawn(move || {
+                    let mut last = [None; 2];
+                    let mut received = 0;
+                    for (sender, i) in rx.iter() {
+                        assert!(last[sender].is_none_or(|last| last < i));
+                        last[sender] = Some(i);
+                        received += 1;
+                    }
+                    received
+                })
+            })
+            .collect();
+
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                let mut capacity = 1;
+                while !done.load(Ordering::Relaxed) {
+                    capacity = capacity % 8 + 1;
+                    // too small for what's unread is fine, it's tried again later
+                    _ = tx.set_capacity(capacity);
+                    thread::yield_now();
+                }
+            });
+            let senders: Vec<_> = (0..2)
+                .map(|sender| {
+                    let tx = tx.clone();
+                    s.spawn(move || {
+                        for i in 0..2000 {
+                            tx.blocking_send((sender, i)).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for sender in senders {
+                sender.join().unwrap();
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+
+        drop(tx);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 4000);
+        }
+    });
+}
+
+#[test]
+fn shrinking_waits_for_unread() {
+    let tx = Channel::new(4);
+    let mut rx = tx.spawn_rx();
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(tx.set_capacity(2), Err(ResizeError::Unread(3)));
+    assert_eq!(tx.capacity(), 4);
+
+    assert_eq!(rx.recv(), Ok(0));
+    tx.set_capacity(2).unwrap();
+    assert_eq!(tx.capacity(), 2);
+    // what was sent before stays readable
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(rx.recv(), Ok(2));
+    tx.send(3).unwrap();
+    tx.send(4).unwrap();
+    assert_eq!(tx.send(5), Err(SendError::Full(5)));
+    assert_eq!(rx.recv(), Ok(3));
+}
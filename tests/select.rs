@@ -0,0 +1,62 @@
+use trotcast::prelude::*;
+
+/// Ready receivers take turns, whatever other selects do in the meantime.
+#[test]
+fn each_select_takes_its_own_turns() {
+    let channels: Vec<_> = (0..3).map(|_| Channel::new(4)).collect();
+    let receivers: Vec<_> = channels.iter().map(|tx| tx.spawn_rx()).collect();
+    for tx in &channels {
+        tx.send(0).unwrap();
+    }
+
+    let mut sel = Select::new();
+    let mut other = Select::new();
+    for rx in &receivers {
+        sel.recv(rx);
+        other.recv(rx);
+    }
+    for turn in 0..9 {
+        assert_eq!(sel.try_select(), Ok(turn % 3));
+        _ = other.try_select();
+        _ = other.try_select();
+    }
+}
+
+#[test]
+fn start_at_carries_turns_over_rebuilt_selects() {
+    let channels: Vec<_> = (0..3).map(|_| Channel::new(4)).collect();
+    let receivers: Vec<_> = channels.iter().map(|tx| tx.spawn_rx()).collect();
+    for tx in &channels {
+        tx.send(0).unwrap();
+    }
+
+    let mut next = 0;
+    for turn in 0..6 {
+        let mut sel = Select::new();
+        for rx in &receivers {
+            sel.recv(rx);
+        }
+        sel.start_at(next);
+        let index = sel.try_select().unwrap();
+        assert_eq!(index, turn % 3);
+        next = index + 1;
+    }
+}
+
+#[test]
+fn biased_select_prefers_the_first() {
+    let first = Channel::new(4);
+    let second = Channel::new(4);
+    let rx_first = first.spawn_rx();
+    let rx_second = second.spawn_rx();
+    second.send(0).unwrap();
+
+    let mut sel = Select::new_biased();
+    sel.recv(&rx_first);
+    sel.recv(&rx_second);
+    assert_eq!(sel.try_select(), Ok(1));
+    first.send(0).unwrap();
+    for _ in 0..3 {
+        assert_eq!(sel.try_select(), Ok(0));
+    }
+}
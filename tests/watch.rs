@@ -0,0 +1,37 @@
+mod common;
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use common::within;
+use trotcast::prelude::*;
+
+/// A send waits for a borrowed value it would replace, and the receiver still sees the
+/// newest value afterwards.
+#[test]
+fn send_waits_for_borrowed_value() {
+    within(Duration::from_secs(10), || {
+        let watch = Watch::new(0);
+        let rx = watch.subscribe();
+        let (sent, sent_all) = mpsc::channel();
+
+        let borrowed = rx.borrow();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=3 {
+                    watch.send(i);
+                    sent.send(i).unwrap();
+                }
+            });
+            // one more value fits next to the borrowed one, the next has to wait
+            assert_eq!(sent_all.recv(), Ok(1));
+            thread::sleep(Duration::from_millis(50));
+            assert!(
+                sent_all.try_recv().is_err(),
+                "the borrowed value was replaced"
+            );
+            assert_eq!(*borrowed, 0);
+            drop(borrowed);
+        });
+        assert_eq!(*rx.borrow(), 3);
+    });
+}